@@ -1,5 +1,5 @@
 use clap::Parser;
-use fdt_parser::Fdt;
+use fdt_parser::{Fdt, PropertyValue};
 use std::io::Write;
 
 /// Simple DTB parser
@@ -31,13 +31,14 @@ fn main() {
     }
 
     for node in fdt.all_nodes() {
+        let node = node.unwrap();
         let space = "\t".repeat(node.level - 1);
         writeln!(file, "{}{}", space, node.name()).unwrap();
 
-        if let Some(cap) = node.compatible() {
-            writeln!(file, "{} -compatible: ", space).unwrap();
-            for cap in cap {
-                writeln!(file, "{}     {:?}", space, cap.unwrap()).unwrap();
+        for prop in node.propertys() {
+            match prop.value() {
+                PropertyValue::Empty => writeln!(file, "{} -{};", space, prop.name).unwrap(),
+                value => writeln!(file, "{} -{} = {};", space, prop.name, value).unwrap(),
             }
         }
 
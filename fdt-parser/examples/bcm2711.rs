@@ -16,6 +16,7 @@ fn main() {
         if i > 40 {
             break;
         }
+        let node = node.unwrap();
         let space = " ".repeat((node.level - 1) * 4);
         println!("{}{}", space, node.name());
 
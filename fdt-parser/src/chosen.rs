@@ -11,13 +11,15 @@ impl<'a> Chosen<'a> {
 
     /// Contains the bootargs, if they exist
     pub fn bootargs(&self) -> Option<&'a str> {
-        self.node.find_property("bootargs").map(|p| p.str())
+        self.node
+            .find_property("bootargs")
+            .and_then(|p| p.try_str().ok())
     }
 
     /// Searches for the node representing `stdout`, if the property exists,
     /// attempting to resolve aliases if the node name doesn't exist as-is
     pub fn stdout(&self) -> Option<Stdout<'a>> {
-        let path = self.node.find_property("stdout-path")?.str();
+        let path = self.node.find_property("stdout-path")?.try_str().ok()?;
         let mut sp = path.split(':');
         let name = sp.next()?;
         let params = sp.next();
@@ -1,18 +1,54 @@
-use crate::{node::Node, read::FdtReader, Fdt, Phandle};
+use crate::{
+    error::*,
+    node::Node,
+    phandle::{PhandleArgs, PhandleArgsIter},
+    property::Array,
+    value::StrList,
+    Fdt, NodeOffset, Specifier,
+};
 
+/// Upper bound on provider hops, so a `clocks` cycle can't loop forever.
+const MAX_CLOCK_HOPS: usize = 16;
+
+/// Clocks whose `assigned-clocks` settings [ClockTree::new] keeps.
+const MAX_ASSIGNED: usize = 16;
+
+/// Iterator over the `clocks` of a consumer, see [Node::clocks]. As an
+/// [Iterator] it ends at the first entry that can't be read, use
+/// [ClocksIter::next_clock] or [Node::try_clocks] to tell that apart from
+/// the end of the list.
 pub struct ClocksIter<'a> {
-    pub fdt: &'a Fdt<'a>,
-    pub prop: Option<FdtReader<'a>>,
+    entries: PhandleArgsIter<'a>,
+    names: Option<StrList<'a>>,
+    index: usize,
 }
 
 impl<'a> ClocksIter<'a> {
-    pub fn new(node: &'a Node<'a>) -> Self {
-        let fdt = node.fdt;
-        let prop = node.find_property("clocks");
-
+    pub fn new(node: &Node<'a>) -> Self {
         Self {
-            fdt,
-            prop: prop.map(|p| p.data),
+            entries: node.parse_phandle_with_args("clocks", "#clock-cells"),
+            names: node.entry_names("clocks"),
+            index: 0,
+        }
+    }
+}
+
+impl<'a> ClocksIter<'a> {
+    /// Like [Iterator::next], but reports malformed `clocks` entries. Null
+    /// entries are skipped, keeping their `clock-names` slot.
+    pub fn next_clock(&mut self) -> Option<FdtResult<'a, ClockRef<'a>>> {
+        loop {
+            let entry = self.entries.next()?;
+            let index = self.index;
+            self.index = self.index.saturating_add(1);
+            if let Err(FdtError::NullPhandle) = entry {
+                continue;
+            }
+            return Some(entry.map(|args| {
+                let mut clock = ClockRef::from(args);
+                clock.name = self.names.and_then(|names| names.iter().nth(index));
+                clock
+            }));
         }
     }
 }
@@ -21,33 +57,287 @@ impl<'a> Iterator for ClocksIter<'a> {
     type Item = ClockRef<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let p = self.prop.as_mut()?;
-        let phandle = Phandle::from(p.take_u32()?);
+        self.next_clock()?.ok()
+    }
+}
 
-        let node = self.fdt.get_node_by_phandle(phandle)?;
-        let mut select = 0;
+#[derive(Clone)]
+pub struct ClockRef<'a> {
+    pub node: Node<'a>,
+    /// second cell of one of `clocks`, 0 when the provider has no
+    /// `#clock-cells`.
+    pub select: usize,
+    /// All cells after the phandle, in the format of the provider's
+    /// `#clock-cells`.
+    pub specifier: Specifier,
+    /// Matching entry of `clock-names`.
+    pub name: Option<&'a str>,
+}
 
-        let cell_size = node
-            .find_property("#clock-cells")
-            .expect("#clock-cells not found")
-            .u32();
+impl<'a> ClockRef<'a> {
+    fn key(&self) -> ClockKey {
+        (self.node.offset(), self.specifier)
+    }
 
-        if cell_size > 0 {
-            select = p.take_u32().expect("invalid clock cells");
-            for _ in 0..cell_size - 1 {
-                p.take_u32();
+    /// Name of the clock in the `clock-output-names` of the provider.
+    pub fn output_name(&self) -> Option<&'a str> {
+        let names = self.node.find_property("clock-output-names")?;
+        StrList::new(names.raw_value()).iter().nth(self.select)
+    }
+}
+
+impl<'a> From<PhandleArgs<'a>> for ClockRef<'a> {
+    fn from(args: PhandleArgs<'a>) -> Self {
+        ClockRef {
+            node: args.provider,
+            select: args.args.first().copied().unwrap_or_default() as usize,
+            specifier: args.args,
+            name: None,
+        }
+    }
+}
+
+/// Configuration a node requests for a clock, one entry of its
+/// `assigned-clocks`, see [Node::assigned_clocks].
+#[derive(Clone)]
+pub struct AssignedClock<'a> {
+    pub clock: ClockRef<'a>,
+    /// Matching entry of `assigned-clock-parents`, the parent to switch the
+    /// clock to.
+    pub parent: Option<ClockRef<'a>>,
+    /// Matching entry of `assigned-clock-rates` or
+    /// `assigned-clock-rates-u64`, in Hz.
+    pub rate: Option<u64>,
+}
+
+impl<'a> Node<'a> {
+    /// The clock named `name` in `clock-names`.
+    pub fn clock_by_name(&self, name: &str) -> FdtResult<'a, ClockRef<'a>> {
+        let mut clocks = ClocksIter::new(self);
+        while let Some(clock) = clocks.next_clock() {
+            let clock = clock?;
+            if clock.name == Some(name) {
+                return Ok(clock);
             }
         }
+        Err(FdtError::NotFound("clock"))
+    }
+
+    /// Parents and rates this node asks to set up for clocks, with
+    /// `assigned-clocks`, `assigned-clock-parents` and
+    /// `assigned-clock-rates`. A null phandle or a rate of 0 in the parents
+    /// or rates leaves that setting unchanged.
+    pub fn assigned_clocks(&self) -> impl Iterator<Item = FdtResult<'a, AssignedClock<'a>>> + 'a {
+        let mut parents = self.parse_phandle_with_args("assigned-clock-parents", "#clock-cells");
+        let rates = self.get::<Array<u32>>("assigned-clock-rates").ok();
+        let rates_u64 = self.get::<Array<u64>>("assigned-clock-rates-u64").ok();
+
+        self.parse_phandle_with_args("assigned-clocks", "#clock-cells")
+            .enumerate()
+            .map(move |(i, clock)| {
+                let parent = match parents.next() {
+                    Some(Ok(parent)) => Some(ClockRef::from(parent)),
+                    Some(Err(FdtError::NullPhandle)) | None => None,
+                    Some(Err(e)) => return Err(e),
+                };
+                let rate = match rates_u64 {
+                    Some(mut rates) => rates.nth(i),
+                    None => rates.and_then(|mut rates| rates.nth(i)).map(u64::from),
+                };
+
+                Ok(AssignedClock {
+                    clock: ClockRef::from(clock?),
+                    parent,
+                    rate: rate.filter(|rate| *rate != 0),
+                })
+            })
+    }
+}
+
+/// Clock providers of a blob and the rates known without a clock driver,
+/// see [Fdt::clock_tree].
+///
+/// A rate is known for a `fixed-clock`, a provider with `clock-frequency`
+/// and a `#clock-cells` of 0, a clock some node sets with
+/// `assigned-clock-rates`, and a `fixed-factor-clock` or a clock switched
+/// with `assigned-clock-parents` fed by any of these.
+pub struct ClockTree<'a> {
+    fdt: &'a Fdt<'a>,
+    /// `assigned-clocks` settings by clock, the first node in the tree to
+    /// set a rate or a parent winning.
+    assigned: [Option<Assigned>; MAX_ASSIGNED],
+    /// Some clocks didn't fit in `assigned`.
+    more_assigned: bool,
+}
+
+/// A clock as a provider offset and specifier.
+type ClockKey = (NodeOffset, Specifier);
+
+/// Rate and parent the `assigned-clocks` of the blob set for a clock.
+#[derive(Clone, Copy)]
+struct Assigned {
+    clock: ClockKey,
+    rate: Option<u64>,
+    parent: Option<ClockKey>,
+}
 
-        Some(ClockRef {
-            node,
-            select: select as _,
+impl Assigned {
+    /// Fill what `other` sets for the same clock and this doesn't yet.
+    fn merge(&mut self, other: &AssignedClock<'_>) {
+        self.rate = self.rate.or(other.rate);
+        self.parent = self.parent.or(other.parent.as_ref().map(ClockRef::key));
+    }
+}
+
+impl<'a> ClockTree<'a> {
+    pub fn new(fdt: &'a Fdt<'a>) -> Self {
+        let mut tree = ClockTree {
+            fdt,
+            assigned: [None; MAX_ASSIGNED],
+            more_assigned: false,
+        };
+        for setting in tree.assigned_clocks() {
+            let key = setting.clock.key();
+            let mut slots = tree.assigned.iter_mut();
+            let slot = slots.find(|slot| slot.is_none_or(|known| known.clock == key));
+            match slot {
+                Some(Some(known)) => known.merge(&setting),
+                Some(slot) => {
+                    let mut assigned = Assigned {
+                        clock: key,
+                        rate: None,
+                        parent: None,
+                    };
+                    assigned.merge(&setting);
+                    *slot = Some(assigned);
+                }
+                None => tree.more_assigned = true,
+            }
+        }
+        tree
+    }
+
+    /// Nodes with `#clock-cells`.
+    pub fn providers(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        self.fdt
+            .readable_nodes()
+            .filter(|node| node.find_property("#clock-cells").is_some())
+    }
+
+    /// Rate of `clock` in Hz, `None` if it can't be known statically.
+    pub fn rate(&self, clock: &ClockRef<'a>) -> Option<u64> {
+        let (mut mult, mut div) = (1u128, 1u128);
+        let mut clock = clock.clone();
+
+        for _ in 0..MAX_CLOCK_HOPS {
+            let assigned = self.assigned(&clock);
+            let rate = assigned.and_then(|assigned| assigned.rate);
+            if let Some(rate) = rate.or_else(|| own_rate(&clock.node)) {
+                return u64::try_from(u128::from(rate).checked_mul(mult)?.checked_div(div)?).ok();
+            }
+            // A clock switched to another parent passes its rate through.
+            if assigned.and_then(|assigned| assigned.parent).is_none() {
+                let (m, d) = fixed_factor(&clock.node)?;
+                mult = mult.checked_mul(m.into())?;
+                div = div.checked_mul(d.into())?;
+            }
+            clock = self.parent(&clock)?;
+        }
+        None
+    }
+
+    /// Rate of the clock of `consumer` named `name` in `clock-names`, or of
+    /// its first clock.
+    pub fn consumer_rate(&self, consumer: &Node<'a>, name: Option<&str>) -> Option<u64> {
+        let clock = match name {
+            Some(name) => consumer.clock_by_name(name).ok()?,
+            None => consumer.clocks().next()?,
+        };
+        self.rate(&clock)
+    }
+
+    /// The clock `clock` is derived from: the parent some node switches it
+    /// to with `assigned-clock-parents`, else the input of a provider with a
+    /// single clock input, such as a `fixed-factor-clock`. `None` for root
+    /// oscillators and for providers that select among several inputs.
+    pub fn parent(&self, clock: &ClockRef<'a>) -> Option<ClockRef<'a>> {
+        match self.assigned(clock).and_then(|assigned| assigned.parent) {
+            Some((provider, args)) => Some(ClockRef::from(PhandleArgs {
+                provider: self.fdt.node_at(provider)?,
+                args,
+            })),
+            None => {
+                let mut inputs = clock.node.clocks();
+                let parent = inputs.next()?;
+                inputs.next().is_none().then_some(parent)
+            }
+        }
+    }
+
+    /// Clocks from the parent of `clock` up to its root oscillator, as far
+    /// as [ClockTree::parent] knows them.
+    pub fn chain<'t>(&'t self, clock: &ClockRef<'a>) -> impl Iterator<Item = ClockRef<'a>> + 't {
+        let mut clock = Some(clock.clone());
+
+        core::iter::from_fn(move || {
+            let parent = self.parent(clock.as_ref()?);
+            clock = parent.clone();
+            parent
         })
+        .take(MAX_CLOCK_HOPS)
+    }
+
+    /// What the `assigned-clocks` of the blob set for `clock`.
+    fn assigned(&self, clock: &ClockRef<'a>) -> Option<Assigned> {
+        let key = clock.key();
+        let mut known = self.assigned.iter().flatten();
+        if let Some(assigned) = known.find(|assigned| assigned.clock == key) {
+            return Some(*assigned);
+        }
+        if !self.more_assigned {
+            return None;
+        }
+        let mut settings = self
+            .assigned_clocks()
+            .filter(|setting| setting.clock.key() == key);
+        let mut assigned = Assigned {
+            clock: key,
+            rate: None,
+            parent: None,
+        };
+        assigned.merge(&settings.next()?);
+        settings.for_each(|setting| assigned.merge(&setting));
+        Some(assigned)
+    }
+
+    /// `assigned-clocks` entries setting a rate or a parent, of every node
+    /// in tree order.
+    fn assigned_clocks(&self) -> impl Iterator<Item = AssignedClock<'a>> + 'a {
+        self.fdt
+            .readable_nodes()
+            .filter(|node| node.find_property("assigned-clocks").is_some())
+            .flat_map(|node| node.assigned_clocks())
+            .filter_map(Result::ok)
+            .filter(|setting| setting.rate.is_some() || setting.parent.is_some())
     }
 }
 
-pub struct ClockRef<'a> {
-    pub node: Node<'a>,
-    /// second cell of one of `clocks`.
-    pub select: usize,
+/// Rate a provider declares for its only clock.
+fn own_rate(node: &Node<'_>) -> Option<u64> {
+    let cells = node.get::<u32>("#clock-cells").ok()?;
+    let is_fixed = node.compatibles().any(|c| c == "fixed-clock");
+    if cells != 0 && !is_fixed {
+        return None;
+    }
+    node.clock_frequency().map(u64::from)
+}
+
+/// `clock-mult` and `clock-div` of a `fixed-factor-clock`.
+fn fixed_factor(node: &Node<'_>) -> Option<(u32, u32)> {
+    if !node.compatibles().any(|c| c == "fixed-factor-clock") {
+        return None;
+    }
+    let mult = node.get::<u32>("clock-mult").ok()?;
+    let div = node.get::<u32>("clock-div").ok().filter(|div| *div != 0)?;
+    Some((mult, div))
 }
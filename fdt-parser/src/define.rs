@@ -1,12 +1,9 @@
 use core::{
-    fmt::{Debug, Display},
+    fmt::{Debug, Display, Write},
     ptr::NonNull,
 };
 
-use crate::{
-    error::*,
-    read::{FdtReader, U32Array},
-};
+use crate::{error::*, read::FdtReader};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Token {
@@ -44,9 +41,12 @@ impl Fdt32 {
     }
 }
 
+/// Missing trailing bytes read as zero.
 impl From<&[u8]> for Fdt32 {
     fn from(value: &[u8]) -> Self {
-        Fdt32(value.get(..4).unwrap().try_into().unwrap())
+        let mut bytes = [0; 4];
+        bytes.iter_mut().zip(value).for_each(|(b, v)| *b = *v);
+        Fdt32(bytes)
     }
 }
 
@@ -69,9 +69,12 @@ impl Fdt64 {
         u64::from_be_bytes(self.0)
     }
 }
+/// Missing trailing bytes read as zero.
 impl From<&[u8]> for Fdt64 {
     fn from(value: &[u8]) -> Self {
-        Self(value.get(..8).unwrap().try_into().unwrap())
+        let mut bytes = [0; 8];
+        bytes.iter_mut().zip(value).for_each(|(b, v)| *b = *v);
+        Self(bytes)
     }
 }
 impl Default for Fdt64 {
@@ -80,16 +83,6 @@ impl Default for Fdt64 {
     }
 }
 
-/// A raw `reg` property value set
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct RawReg<'a> {
-    /// Big-endian encoded bytes making up the address portion of the property.
-    /// Length will always be a multiple of 4 bytes.
-    pub address: &'a [u8],
-    /// Big-endian encoded bytes making up the size portion of the property.
-    /// Length will always be a multiple of 4 bytes.
-    pub size: &'a [u8],
-}
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct FdtHeader {
@@ -125,28 +118,84 @@ impl FdtHeader {
         }
     }
 
+    /// Header size of a blob of the given `version`, older layouts lack the
+    /// trailing fields.
+    pub(crate) fn len_for(version: u32) -> usize {
+        let fields: usize = match version {
+            0..=1 => 7,
+            2 => 8,
+            3..=16 => 9,
+            _ => 10,
+        };
+        fields.saturating_mul(size_of::<Fdt32>())
+    }
+
+    /// Size of this header in the blob.
+    pub(crate) fn len(&self) -> usize {
+        Self::len_for(self.version.get())
+    }
+
+    /// End of the block starting at `start` when the header doesn't give its
+    /// size: the start of the next block, or the end of the blob.
+    fn block_end(&self, start: usize) -> usize {
+        [
+            self.off_mem_rsvmap,
+            self.off_dt_struct,
+            self.off_dt_strings,
+            self.totalsize,
+        ]
+        .iter()
+        .map(|offset| offset.get() as usize)
+        .filter(|&offset| offset > start)
+        .min()
+        .unwrap_or(start)
+    }
+
     pub(crate) fn struct_range(&self) -> core::ops::Range<usize> {
         let start = self.off_dt_struct.get() as usize;
-        let end = start + self.size_dt_struct.get() as usize;
+        let end = if self.version.get() >= 17 {
+            start.saturating_add(self.size_dt_struct.get() as usize)
+        } else {
+            self.block_end(start)
+        };
 
         start..end
     }
 
     pub(crate) fn strings_range(&self) -> core::ops::Range<usize> {
         let start = self.off_dt_strings.get() as usize;
-        let end = start + self.size_dt_strings.get() as usize;
+        let end = if self.version.get() >= 3 {
+            start.saturating_add(self.size_dt_strings.get() as usize)
+        } else {
+            self.block_end(start)
+        };
         start..end
     }
 
+    /// Read the header of a blob of any version from 1 on, the fields its
+    /// layout lacks read as zero.
     pub fn from_bytes(bytes: &[u8]) -> FdtResult<'static, Self> {
-        if bytes.len() < size_of::<FdtHeader>() {
-            return Err(FdtError::Eof);
-        }
-
-        unsafe {
-            let ptr: *const FdtHeader = bytes.as_ptr().cast();
-            Ok(ptr.read())
-        }
+        let version = Fdt32::from(bytes.get(20..).unwrap_or_default()).get();
+        let raw = bytes.get(..Self::len_for(version)).ok_or(FdtError::Eof)?;
+        let field = |i: usize| {
+            Fdt32::from(
+                raw.get(i.saturating_mul(size_of::<Fdt32>())..)
+                    .unwrap_or_default(),
+            )
+        };
+
+        Ok(Self {
+            magic: field(0),
+            totalsize: field(1),
+            off_dt_struct: field(2),
+            off_dt_strings: field(3),
+            off_mem_rsvmap: field(4),
+            version: field(5),
+            last_comp_version: field(6),
+            boot_cpuid_phys: field(7),
+            size_dt_strings: field(8),
+            size_dt_struct: field(9),
+        })
     }
 
     pub fn from_ptr(ptr: NonNull<u8>) -> FdtResult<'static, Self> {
@@ -200,12 +249,181 @@ impl Debug for MemoryRegion {
     }
 }
 
+/// Bus address or size made of up to 4 cells, such as the 3-cell addresses
+/// of PCI buses.
+///
+/// Addresses compare by value, whatever their number of cells.
+#[derive(Clone, Copy, Default)]
+pub struct CellAddress {
+    value: u128,
+    cells: u8,
+}
+
+impl CellAddress {
+    /// Largest number of cells an address can have.
+    pub const MAX_CELLS: usize = 4;
+
+    /// Address made of `cells`, most significant first. `None` for more than
+    /// [CellAddress::MAX_CELLS] cells.
+    pub fn new(cells: &[u32]) -> Option<Self> {
+        if cells.len() > Self::MAX_CELLS {
+            return None;
+        }
+        Some(Self {
+            value: cells
+                .iter()
+                .fold(0, |value, cell| (value << 32) | *cell as u128),
+            cells: cells.len() as _,
+        })
+    }
+
+    /// Number of cells the address was read from.
+    pub fn cells(&self) -> usize {
+        self.cells as _
+    }
+
+    /// The `i`-th cell, most significant first.
+    pub fn cell(&self, i: usize) -> Option<u32> {
+        let shift = self
+            .cells()
+            .checked_sub(i.checked_add(1)?)?
+            .checked_mul(32)?;
+        Some(self.value.checked_shr(u32::try_from(shift).ok()?)? as u32)
+    }
+
+    pub fn as_u128(&self) -> u128 {
+        self.value
+    }
+
+    /// Sum of both addresses, as wide as the widest one. `None` on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Self::with_cells(
+            self.value.checked_add(rhs.value)?,
+            self.cells.max(rhs.cells),
+        )
+    }
+
+    /// Difference of both addresses, as wide as the widest one. `None` if
+    /// `rhs` is larger.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Self::with_cells(
+            self.value.checked_sub(rhs.value)?,
+            self.cells.max(rhs.cells),
+        )
+    }
+
+    /// Offset of this address in the `size` bytes starting at `base`.
+    pub fn offset_in(self, base: Self, size: Self) -> Option<Self> {
+        self.checked_sub(base).filter(|offset| *offset < size)
+    }
+
+    fn with_cells(value: u128, cells: u8) -> Option<Self> {
+        let bits = u32::from(cells).saturating_mul(32);
+        if value.checked_shr(bits).is_some_and(|high| high != 0) {
+            return None;
+        }
+        Some(Self { value, cells })
+    }
+}
+
+impl From<u32> for CellAddress {
+    fn from(value: u32) -> Self {
+        Self {
+            value: value as _,
+            cells: 1,
+        }
+    }
+}
+
+impl From<u64> for CellAddress {
+    fn from(value: u64) -> Self {
+        Self {
+            value: value as _,
+            cells: 2,
+        }
+    }
+}
+
+/// Fails with [FdtError::AddressOverflow] if the address needs more than 64
+/// bits.
+impl TryFrom<CellAddress> for u64 {
+    type Error = FdtError<'static>;
+
+    fn try_from(value: CellAddress) -> Result<Self, Self::Error> {
+        u64::try_from(value.value).map_err(|_| FdtError::AddressOverflow)
+    }
+}
+
+/// Fails with [FdtError::AddressOverflow] if the address doesn't fit in a
+/// `usize`.
+impl TryFrom<CellAddress> for usize {
+    type Error = FdtError<'static>;
+
+    fn try_from(value: CellAddress) -> Result<Self, Self::Error> {
+        usize::try_from(value.value).map_err(|_| FdtError::AddressOverflow)
+    }
+}
+
+impl PartialEq for CellAddress {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for CellAddress {}
+
+impl PartialEq<u64> for CellAddress {
+    fn eq(&self, other: &u64) -> bool {
+        self.value == *other as u128
+    }
+}
+
+impl PartialOrd for CellAddress {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CellAddress {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl core::hash::Hash for CellAddress {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl Debug for CellAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:#x}", self.value)
+    }
+}
+
+impl Display for CellAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:#x}", self.value)
+    }
+}
+
+impl core::fmt::LowerHex for CellAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::LowerHex::fmt(&self.value, f)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct FdtReg {
-    /// parent bus address
-    pub address: u64,
+    /// CPU physical address, or the child bus address when it has none
+    pub address: CellAddress,
+    /// CPU physical address, `None` if some bus on the way to the root is
+    /// not memory-mapped, see [crate::Node::translate_address]
+    pub cpu_address: Option<CellAddress>,
     /// child bus address
-    pub child_bus_address: u64,
+    pub child_bus_address: CellAddress,
+    /// `None` if `#size-cells` is 0 or the size doesn't fit in a `usize`.
     pub size: Option<usize>,
 }
 
@@ -226,34 +444,43 @@ impl Debug for FdtReg {
 
 /// Range mapping child bus addresses to parent bus addresses
 #[derive(Clone)]
-pub struct FdtRange<'a> {
-    data_child: &'a [u8],
-    data_parent: &'a [u8],
+pub struct FdtRange {
+    child_bus_address: CellAddress,
+    parent_bus_address: CellAddress,
     /// Size of range
-    pub size: u64,
+    pub size: CellAddress,
 }
 
-impl<'a> FdtRange<'a> {
-    pub fn child_bus_address(&self) -> U32Array<'a> {
-        U32Array::new(self.data_child)
+impl FdtRange {
+    pub fn child_bus_address(&self) -> CellAddress {
+        self.child_bus_address
+    }
+
+    pub fn parent_bus_address(&self) -> CellAddress {
+        self.parent_bus_address
+    }
+
+    /// `address` on the parent bus, if it is on the child bus side of this
+    /// range.
+    pub fn translate(&self, address: CellAddress) -> Option<CellAddress> {
+        let offset = address.offset_in(self.child_bus_address, self.size)?;
+        self.parent_bus_address.checked_add(offset)
     }
 
-    pub fn parent_bus_address(&self) -> U32Array<'a> {
-        U32Array::new(self.data_parent)
+    /// `address` on the child bus, if it is on the parent bus side of this
+    /// range.
+    pub fn translate_to_child(&self, address: CellAddress) -> Option<CellAddress> {
+        let offset = address.offset_in(self.parent_bus_address, self.size)?;
+        self.child_bus_address.checked_add(offset)
     }
 }
 
-impl Debug for FdtRange<'_> {
+impl Debug for FdtRange {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.write_str("Range {{ child_bus_address: [ ")?;
-        for addr in self.child_bus_address() {
-            f.write_fmt(format_args!("{:#x} ", addr))?;
-        }
-        f.write_str("], parent_bus_address: [ ")?;
-        for addr in self.parent_bus_address() {
-            f.write_fmt(format_args!("{:#x} ", addr))?;
-        }
-        f.write_fmt(format_args!("], size: {:#x}", self.size))
+        f.write_fmt(format_args!(
+            "Range {{ child_bus_address: {:#x}, parent_bus_address: {:#x}, size: {:#x} }}",
+            self.child_bus_address, self.parent_bus_address, self.size
+        ))
     }
 }
 
@@ -283,32 +510,28 @@ impl<'a> FdtRangeSilce<'a> {
     pub fn iter(&self) -> FdtRangeIter<'a> {
         FdtRangeIter { s: self.clone() }
     }
+
+    /// An empty `ranges` maps child bus addresses unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.reader.is_empty()
+    }
 }
 #[derive(Clone)]
 pub struct FdtRangeIter<'a> {
     s: FdtRangeSilce<'a>,
 }
 
-impl<'a> Iterator for FdtRangeIter<'a> {
-    type Item = FdtRange<'a>;
+impl Iterator for FdtRangeIter<'_> {
+    type Item = FdtRange;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let child_address_bytes = self.s.address_cell as usize * size_of::<u32>();
-        let data_child = self.s.reader.take(child_address_bytes)?;
-
-        let parent_address_bytes = self.s.address_cell_parent as usize * size_of::<u32>();
-        let data_parent = self.s.reader.take(parent_address_bytes)?;
-
-        // let child_bus_address = self.s.reader.take_by_cell_size(self.s.address_cell)?;
-        // let parent_bus_address = self
-        //     .s
-        //     .reader
-        //     .take_by_cell_size(self.s.address_cell_parent)?;
-        let size = self.s.reader.take_by_cell_size(self.s.size_cell)?;
+        let child_bus_address = self.s.reader.take_address(self.s.address_cell)?;
+        let parent_bus_address = self.s.reader.take_address(self.s.address_cell_parent)?;
+        let size = self.s.reader.take_address(self.s.size_cell)?;
         Some(FdtRange {
+            child_bus_address,
+            parent_bus_address,
             size,
-            data_child,
-            data_parent,
         })
     }
 }
@@ -333,3 +556,75 @@ impl Display for Phandle {
         write!(f, "<{:#x}>", self.0)
     }
 }
+
+/// Stable handle to a node: the offset of its `BEGIN_NODE` token in the
+/// structure block, like libfdt's node offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct NodeOffset(pub(crate) usize);
+
+impl From<usize> for NodeOffset {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
+impl NodeOffset {
+    pub fn as_usize(&self) -> usize {
+        self.0
+    }
+}
+
+impl Display for NodeOffset {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// Cells of a specifier, such as the interrupt specifier a nexus maps to,
+/// copied out of the blob. Holds up to [Specifier::MAX_CELLS] cells like
+/// Linux `of_phandle_args`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Specifier {
+    cells: [u32; Specifier::MAX_CELLS],
+    len: usize,
+}
+
+impl Specifier {
+    pub const MAX_CELLS: usize = 16;
+
+    /// `None` if there are more than [Specifier::MAX_CELLS] cells.
+    pub fn new(cells: &[u32]) -> Option<Self> {
+        let mut out = Self {
+            cells: [0; Self::MAX_CELLS],
+            len: cells.len(),
+        };
+        out.cells.get_mut(..cells.len())?.copy_from_slice(cells);
+        Some(out)
+    }
+
+    pub fn as_slice(&self) -> &[u32] {
+        self.cells.get(..self.len).unwrap_or_default()
+    }
+}
+
+impl core::ops::Deref for Specifier {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        self.as_slice()
+    }
+}
+
+impl Debug for Specifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_char('<')?;
+        for (i, cell) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            write!(f, "{:#x}", cell)?;
+        }
+        f.write_char('>')
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    error::{FdtError, FdtResult},
+    node::{translate, Node},
+    CellAddress, FdtRange, Specifier,
+};
+
+/// Window of CPU physical memory a device reaches by DMA, see
+/// [Node::dma_windows].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmaWindow {
+    /// Start of the window as the CPU sees it.
+    pub cpu_address: CellAddress,
+    /// Start of the window as the device sees it.
+    pub dma_address: CellAddress,
+    pub size: CellAddress,
+}
+
+impl DmaWindow {
+    /// First CPU address past the window, `None` if it is past the largest
+    /// [CellAddress].
+    pub fn cpu_end(&self) -> Option<CellAddress> {
+        self.cpu_address.checked_add(self.size)
+    }
+}
+
+/// A DMA channel of a consumer, one entry of its `dmas`, see
+/// [Node::dma_channels].
+#[derive(Clone)]
+pub struct DmaChannel<'a> {
+    pub controller: Node<'a>,
+    /// Specifier in the format of the controller's `#dma-cells`, such as the
+    /// DREQ line.
+    pub specifier: Specifier,
+    /// Matching entry of `dma-names`, such as `"rx"` or `"tx"`.
+    pub name: Option<&'a str>,
+}
+
+impl DmaChannel<'_> {
+    /// Limits of the controller.
+    pub fn limits(&self) -> DmaLimits {
+        self.controller.dma_limits()
+    }
+}
+
+/// Channels and request lines a DMA controller offers, see
+/// [Node::dma_limits].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DmaLimits {
+    /// `dma-channels`
+    pub channels: Option<u32>,
+    /// `dma-requests`
+    pub requests: Option<u32>,
+    /// `dma-channel-mask`, or a vendor variant such as
+    /// `brcm,dma-channel-mask`: bit `n` set if channel `n` is usable.
+    pub channel_mask: Option<u64>,
+}
+
+impl<'a> Node<'a> {
+    /// DMA channels of this consumer, from `dmas` and `dma-names`. Ends with
+    /// an error on an entry that can't be read.
+    pub fn dma_channels(&self) -> impl Iterator<Item = FdtResult<'a, DmaChannel<'a>>> + 'a {
+        let names = self.entry_names("dmas");
+
+        self.parse_phandle_with_args("dmas", "#dma-cells")
+            .enumerate()
+            .map(move |(i, channel)| {
+                let channel = channel?;
+                Ok(DmaChannel {
+                    controller: channel.provider,
+                    specifier: channel.args,
+                    name: names.and_then(|names| names.iter().nth(i)),
+                })
+            })
+    }
+
+    /// The DMA channel named `name` in `dma-names`.
+    pub fn dma_channel_by_name(&self, name: &str) -> FdtResult<'a, DmaChannel<'a>> {
+        self.dma_channels()
+            .find(|channel| match channel {
+                Ok(channel) => channel.name == Some(name),
+                Err(_) => true,
+            })
+            .ok_or(FdtError::NotFound("dma channel"))?
+    }
+
+    /// Limits of this DMA controller.
+    pub fn dma_limits(&self) -> DmaLimits {
+        let mask = self
+            .propertys()
+            .find(|p| p.name == "dma-channel-mask" || p.name.ends_with(",dma-channel-mask"));
+
+        DmaLimits {
+            channels: self.get("dma-channels").ok().flatten(),
+            requests: self.get("dma-requests").ok().flatten(),
+            channel_mask: mask.and_then(|mask| match mask.raw_value().len() {
+                4 => mask.try_u32().ok().map(u64::from),
+                _ => mask.try_u64().ok(),
+            }),
+        }
+    }
+
+    /// Entries of the `dma-ranges` of this node, mapping DMA addresses of its
+    /// children to addresses on its parent bus. `None` if it has none, empty
+    /// when the children see the parent bus unchanged.
+    pub fn dma_ranges(&self) -> Option<impl Iterator<Item = FdtRange> + 'a> {
+        self.ranges_of("dma-ranges").map(|ranges| ranges.iter())
+    }
+
+    /// CPU physical address of `address`, an address this device emits for
+    /// DMA, applying the `dma-ranges` of every ancestor up to the root.
+    ///
+    /// Like Linux, a bus without `dma-ranges` passes addresses unchanged.
+    pub fn dma_to_cpu(&self, address: CellAddress) -> FdtResult<'a, CellAddress> {
+        let buses = self.bus_ranges("dma-ranges").rev();
+        translate(address, buses, true, FdtRange::translate)
+    }
+
+    /// Address this device must use for DMA to the CPU physical `address`,
+    /// the inverse of [Node::dma_to_cpu].
+    pub fn cpu_to_dma(&self, address: CellAddress) -> FdtResult<'a, CellAddress> {
+        let buses = self.bus_ranges("dma-ranges");
+        translate(address, buses, true, FdtRange::translate_to_child)
+    }
+
+    /// The memory this device reaches by DMA: one window per entry of the
+    /// nearest non-empty `dma-ranges` above it. Empty when no bus limits or
+    /// offsets DMA, and addresses are the same for the device and the CPU.
+    ///
+    /// Use it to size bounce buffers or DMA zones.
+    pub fn dma_windows(&self) -> impl Iterator<Item = DmaWindow> + 'a {
+        // Buses are numbered from the outermost one.
+        let nearest = self
+            .bus_ranges("dma-ranges")
+            .enumerate()
+            .rev()
+            .find_map(|(i, ranges)| Some((i, ranges.filter(|r| !r.is_empty())?)));
+        let node = self.clone();
+
+        nearest.into_iter().flat_map(move |(i, ranges)| {
+            let node = node.clone();
+            ranges.iter().filter_map(move |range| {
+                let above = node.bus_ranges("dma-ranges").take(i).rev();
+                let cpu_address =
+                    translate(range.parent_bus_address(), above, true, FdtRange::translate);
+                Some(DmaWindow {
+                    cpu_address: cpu_address.ok()?,
+                    dma_address: range.child_bus_address(),
+                    size: range.size,
+                })
+            })
+        })
+    }
+
+    /// Whether DMA from this device is cache coherent: the nearest
+    /// `dma-coherent` or `dma-noncoherent` on the node or its ancestors,
+    /// non-coherent when there is none.
+    pub fn is_dma_coherent(&self) -> bool {
+        core::iter::once(self.clone())
+            .chain(self.ancestors())
+            .find_map(|node| {
+                if node.find_property("dma-coherent").is_some() {
+                    Some(true)
+                } else if node.find_property("dma-noncoherent").is_some() {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+}
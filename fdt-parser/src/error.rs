@@ -18,6 +18,85 @@ pub enum FdtError<'a> {
 
     MissingProperty,
 
+    /// A bus on the way to the root has no `ranges`, its addresses don't map
+    /// to CPU addresses.
+    NotMemoryMapped,
+
+    /// An address is outside every `ranges` entry of its bus.
+    NoMatchingRange,
+
+    /// A [crate::CellAddress] doesn't fit the requested integer type.
+    AddressOverflow,
+
+    /// A property value is not the size the requested type needs.
+    SizeMismatch {
+        expected: usize,
+        found: usize,
+    },
+
+    /// Nodes are nested deeper than the walk supports.
+    TooDeep,
+
+    /// A phandle that no node has.
+    DanglingPhandle(crate::Phandle),
+
+    /// An entry of a phandle list left empty with a phandle of 0.
+    NullPhandle,
+
+    /// Following `interrupt-parent` leads back to a controller already
+    /// visited.
+    InterruptParentLoop,
+
+    /// `totalsize` is smaller than the header or larger than the data.
+    TotalSizeOutOfBounds,
+
+    /// The structure block doesn't fit in the blob.
+    StructBlockOutOfBounds,
+
+    /// The strings block doesn't fit in the blob.
+    StringsBlockOutOfBounds,
+
+    /// The memory reservation block doesn't fit in the blob or misses its
+    /// terminating entry.
+    MemRsvmapOutOfBounds,
+
+    /// Two blocks share some bytes.
+    BlocksOverlap,
+
+    /// A block or its size isn't aligned as the specification requires.
+    Misaligned,
+
+    /// The blob layout is not one this crate reads.
+    UnsupportedVersion {
+        version: u32,
+        last_comp_version: u32,
+    },
+
+    /// `BEGIN_NODE` and `END_NODE` tokens don't pair up into a single root.
+    UnbalancedNodes,
+
+    /// The structure block isn't terminated by an `END` token.
+    MissingEnd,
+
+    /// A token, property or node name runs past the structure block.
+    TruncatedStruct,
+
+    /// Unknown token, or one that is not allowed at this structure block
+    /// offset.
+    BadToken {
+        token: u32,
+        offset: usize,
+    },
+
+    /// A property name offset outside the strings block.
+    BadStringOffset(u32),
+
+    /// The caller provided buffer is too small.
+    BufferTooSmall,
+
+    /// The [crate::FdtIndex] was built over another blob.
+    IndexMismatch,
+
     Utf8Parse {
         data: &'a [u8],
     },
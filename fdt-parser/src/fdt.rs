@@ -1,8 +1,9 @@
-use core::{iter, ptr::NonNull};
+use core::{fmt::Display, iter, ptr::NonNull};
 
 use crate::{
-    chosen::Chosen, error::*, memory::Memory, meta::MetaData, node::Node, read::FdtReader,
-    FdtHeader, MemoryRegion, Phandle, Token,
+    chosen::Chosen, clocks::ClockTree, error::*, index::FdtIndex, memory::Memory, meta::MetaData,
+    node::Node, read::FdtReader, thermal::ThermalZones, FdtHeader, MemoryRegion, NodeOffset,
+    Phandle, Token,
 };
 
 /// The reference to the FDT raw data.
@@ -10,6 +11,7 @@ use crate::{
 pub struct Fdt<'a> {
     pub(crate) header: FdtHeader,
     pub(crate) data: &'a [u8],
+    pub(crate) index: Option<&'a FdtIndex<'a>>,
 }
 
 impl<'a> Fdt<'a> {
@@ -19,7 +21,22 @@ impl<'a> Fdt<'a> {
 
         header.valid_magic()?;
 
-        Ok(Self { header, data })
+        Ok(Self {
+            header,
+            data,
+            index: None,
+        })
+    }
+
+    /// Use `index` for phandle, path and compatible lookups.
+    ///
+    /// The index must have been built over this same blob.
+    pub fn with_index(mut self, index: &'a FdtIndex<'a>) -> FdtResult<'a, Self> {
+        if !index.belongs_to(&self) {
+            return Err(FdtError::IndexMismatch);
+        }
+        self.index = Some(index);
+        Ok(self)
     }
 
     /// Create a new FDT from a pointer.
@@ -32,7 +49,7 @@ impl<'a> Fdt<'a> {
     }
 
     fn reader(&'a self, offset: usize) -> FdtReader<'a> {
-        FdtReader::new(&self.data[offset..])
+        FdtReader::new(self.data.get(offset..).unwrap_or_default())
     }
 
     pub fn total_size(&self) -> usize {
@@ -73,35 +90,154 @@ impl<'a> Fdt<'a> {
         self.find_nodes("/reserved-memory")
     }
 
-    pub(crate) fn get_str(&'a self, offset: usize) -> FdtResult<'a, &'a str> {
-        let string_bytes = &self.data[self.header.strings_range()];
-        let reader = FdtReader::new(&string_bytes[offset..]);
+    pub(crate) fn get_str(&self, offset: usize) -> FdtResult<'a, &'a str> {
+        let data = self.data;
+        let string_bytes = data.get(self.header.strings_range()).unwrap_or_default();
+        let reader = FdtReader::new(string_bytes.get(offset..).unwrap_or_default());
         reader.peek_str()
     }
 
-    pub fn all_nodes(&'a self) -> impl Iterator<Item = Node<'a>> {
+    /// All nodes, depth-first, supporting trees up to [DEFAULT_DEPTH] levels
+    /// deep. Yields the error that ends the walk, such as
+    /// [FdtError::TooDeep] for a node nested deeper, see
+    /// [Fdt::try_all_nodes].
+    pub fn all_nodes(&'a self) -> impl Iterator<Item = FdtResult<'a, Node<'a>>> {
         self.new_fdt_itr()
     }
 
+    /// All nodes, depth-first, supporting trees up to `DEPTH` levels deep.
+    ///
+    /// Yields [FdtError::TooDeep] and stops when a node is nested deeper.
+    pub fn try_all_nodes<const DEPTH: usize>(
+        &'a self,
+    ) -> impl Iterator<Item = FdtResult<'a, Node<'a>>> {
+        FdtIter::<DEPTH>::new(self)
+    }
+
+    /// The nodes of [Fdt::all_nodes] up to the first error, for the lookups
+    /// that have no way to report one.
+    pub(crate) fn readable_nodes(&'a self) -> impl Iterator<Item = Node<'a>> {
+        self.all_nodes().map_while(Result::ok)
+    }
+
     fn new_fdt_itr(&'a self) -> FdtIter<'a> {
-        let struct_bytes = &self.data[self.header.struct_range()];
-        let reader = FdtReader::new(struct_bytes);
-        FdtIter {
-            fdt: self,
-            current_level: 0,
-            reader,
-            stack: Default::default(),
-            node_reader: None,
-            node_name: "",
+        FdtIter::new(self)
+    }
+
+    pub(crate) fn struct_bytes(&self) -> &'a [u8] {
+        let data = self.data;
+        data.get(self.header.struct_range()).unwrap_or_default()
+    }
+
+    /// Reader over the structure block from `offset`.
+    pub(crate) fn struct_reader(&self, offset: usize) -> FdtReader<'a> {
+        let struct_bytes = self.struct_bytes();
+        let reader = FdtReader::new(struct_bytes.get(offset..).unwrap_or_default());
+        if self.header.version.get() < 16 {
+            reader.with_legacy_base(struct_bytes.as_ptr() as usize)
+        } else {
+            reader
+        }
+    }
+
+    /// Struct block offset of the next token `reader` would read.
+    pub(crate) fn struct_offset(&self, reader: &FdtReader<'a>) -> usize {
+        self.struct_bytes()
+            .len()
+            .saturating_sub(reader.remaining().len())
+    }
+
+    /// Get back the node identified by `offset`, see [Node::offset].
+    ///
+    /// Its inherited metadata (`#address-cells`, `ranges`,
+    /// `interrupt-parent`...) is rebuilt by decoding only its ancestors. With
+    /// an [FdtIndex] attached they are found through the parent links of the
+    /// index, in time proportional to the depth of the node. Without one
+    /// they are found by skipping the subtrees before the node token by
+    /// token, which is linear in the size of the structure block; use
+    /// [Node::child_at] when the parent is at hand. Returns `None` if
+    /// `offset` is not the start of a node.
+    pub fn node_at(&'a self, offset: NodeOffset) -> Option<Node<'a>> {
+        let mut reader = self.struct_reader(offset.0);
+        if !offset.0.is_multiple_of(size_of::<u32>()) || reader.take_token()? != Token::BeginNode {
+            return None;
         }
+        self.ancestor_at(offset.0, usize::MAX)
+            .filter(|node| node.offset == offset.0)
+    }
+
+    /// The node at `level` on the way from the root down to the node whose
+    /// `BEGIN_NODE` token is at struct block `offset`.
+    pub(crate) fn ancestor_at(&'a self, offset: usize, level: usize) -> Option<Node<'a>> {
+        self.descend(offset, level, |_| Ok::<_, ()>(()))
+            .ok()
+            .flatten()
+    }
+
+    /// Walk from the root down to the node at `level` on the way to struct
+    /// block `offset`, calling `visit` on every node of that path.
+    ///
+    /// Only the nodes on that path are decoded, the subtrees of their other
+    /// children are skipped token by token.
+    pub(crate) fn descend<E>(
+        &'a self,
+        offset: usize,
+        level: usize,
+        mut visit: impl FnMut(&Node<'a>) -> Result<(), E>,
+    ) -> Result<Option<Node<'a>>, E> {
+        let mut node = match self.all_nodes().next() {
+            Some(Ok(root)) => root,
+            _ => return Ok(None),
+        };
+        visit(&node)?;
+        while node.offset != offset && node.level < level {
+            let meta = node.meta.merge(&node.meta_parents);
+            let child = match self.index {
+                Some(index) => index.child_towards(node.offset, offset),
+                None => node
+                    .child_ranges()
+                    .find(|range| range.contains(&offset))
+                    .map(|range| range.start),
+            };
+            let child = match child {
+                Some(child) => child,
+                None => return Ok(None),
+            };
+            node = match FdtIter::<1>::subtree(self, child, node.level.saturating_add(1), meta)
+                .next()
+            {
+                Some(Ok(child)) => child,
+                _ => return Ok(None),
+            };
+            visit(&node)?;
+        }
+        Ok(Some(node))
+    }
+
+    /// Full path of `node`, such as `/soc/serial@7e215040`.
+    pub fn path_of(&'a self, node: &Node<'a>) -> NodePath<'a> {
+        NodePath { node: node.clone() }
     }
 
     pub fn chosen(&'a self) -> Option<Chosen<'a>> {
         self.find_nodes("/chosen").next().map(Chosen::new)
     }
 
+    pub fn clock_tree(&'a self) -> ClockTree<'a> {
+        ClockTree::new(self)
+    }
+
+    pub fn thermal_zones(&'a self) -> Option<ThermalZones<'a>> {
+        self.find_nodes("/thermal-zones")
+            .next()
+            .map(ThermalZones::new)
+    }
+
     pub fn get_node_by_phandle(&'a self, phandle: Phandle) -> Option<Node<'a>> {
-        self.all_nodes()
+        if let Some(index) = self.index {
+            return self.node_at(index.find_phandle(phandle)?);
+        }
+        self.readable_nodes()
             .find(|x| match x.phandle() {
                 Some(p) => p.eq(&phandle),
                 None => false,
@@ -110,13 +246,29 @@ impl<'a> Fdt<'a> {
     }
 
     pub fn get_node_by_name(&'a self, name: &str) -> Option<Node<'a>> {
-        self.all_nodes().find(|x| x.name().eq(name)).clone()
+        self.readable_nodes().find(|x| x.name().eq(name)).clone()
     }
 
     pub fn find_compatible(&'a self, with: &'a [&'a str]) -> impl Iterator<Item = Node<'a>> + 'a {
-        let mut all = self.all_nodes();
+        let mut all = self.readable_nodes();
+        let mut indexed = self.index.map(|index| {
+            let mut after = None;
+            iter::from_fn(move || loop {
+                let next = with
+                    .iter()
+                    .filter_map(|want| index.next_compatible(want, after))
+                    .min()?;
+                after = Some(next);
+                if let Some(node) = index.entry_offset(next).and_then(|o| self.node_at(o)) {
+                    return Some(node);
+                }
+            })
+        });
 
         iter::from_fn(move || loop {
+            if let Some(indexed) = &mut indexed {
+                return indexed.next();
+            }
             let node = all.next()?;
             let caps = node.compatibles();
             for cap in caps {
@@ -130,21 +282,57 @@ impl<'a> Fdt<'a> {
     }
 
     /// if path start with '/' then search by path, else search by aliases
+    ///
+    /// Every component but the last leads to the first child it matches,
+    /// the last one yields every matching child. A component only compares
+    /// the unit address when it has one, so `/soc/serial` finds all the
+    /// `serial@...` children of `/soc`. The same nodes are found with or
+    /// without an index.
     pub fn find_nodes(&'a self, path: &'a str) -> impl Iterator<Item = Node<'a>> + 'a {
         let path = if path.starts_with("/") {
-            path
+            Some(path)
         } else {
-            self.find_aliase(path).expect("aliase not found")
+            self.find_aliase(path)
         };
 
-        IterFindNode::new(self.new_fdt_itr(), path)
+        let mut indexed = path.and_then(|path| {
+            self.index
+                .map(|index| index.find_path_all(path).filter_map(|o| self.node_at(o)))
+        });
+        let mut walk = path.map(|path| self.walk_path(path));
+
+        iter::from_fn(move || match (&mut indexed, &mut walk) {
+            (Some(indexed), _) => indexed.next(),
+            (None, Some(walk)) => walk.next(),
+            _ => None,
+        })
+    }
+
+    /// [Fdt::find_nodes] without an index: follow the first child matching
+    /// each component of `path`, then yield every child of that node
+    /// matching the last one.
+    fn walk_path(&'a self, path: &'a str) -> impl Iterator<Item = Node<'a>> + 'a {
+        let (mut dir, last) = split_path(path);
+        let root = self.readable_nodes().next();
+        let parent = root.clone().and_then(|root| {
+            dir.try_fold(root, |node, part| {
+                node.children().find(|c| name_matches(c.name, part))
+            })
+        });
+
+        let matches = parent
+            .filter(|_| !last.is_empty())
+            .into_iter()
+            .flat_map(move |p| p.children().filter(move |c| name_matches(c.name, last)));
+
+        root.filter(|_| last.is_empty()).into_iter().chain(matches)
     }
 
     pub fn find_aliase(&'a self, name: &str) -> Option<&'a str> {
         let aliases = self.find_nodes("/aliases").next()?;
         for prop in aliases.propertys() {
             if prop.name.eq(name) {
-                return Some(prop.str());
+                return prop.try_str().ok();
             }
         }
         None
@@ -155,112 +343,170 @@ impl<'a> Fdt<'a> {
     }
 }
 
-pub struct FdtIter<'a> {
+/// Displays the full path of a node, see [Fdt::path_of].
+pub struct NodePath<'a> {
+    node: Node<'a>,
+}
+
+impl Display for NodePath<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.node.path(f)
+    }
+}
+
+/// Nesting depth [Fdt::all_nodes] and the other tree walks support, use
+/// [Fdt::try_all_nodes] to walk deeper trees.
+pub const DEFAULT_DEPTH: usize = 16;
+
+pub struct FdtIter<'a, const DEPTH: usize = DEFAULT_DEPTH> {
     fdt: &'a Fdt<'a>,
+    /// Level of the parent of the first node yielded, the iteration stops once
+    /// the walk climbs back to it.
+    base_level: usize,
     current_level: usize,
     reader: FdtReader<'a>,
-    stack: [MetaData<'a>; 12],
+    /// Metadata inherited by the first node yielded.
+    meta_base: MetaData,
+    stack: [MetaData; DEPTH],
     node_reader: Option<FdtReader<'a>>,
     node_name: &'a str,
+    node_offset: usize,
+    done: bool,
+    error: Option<FdtError<'a>>,
 }
 
-impl<'a> FdtIter<'a> {
-    fn get_meta_parent(&self) -> MetaData<'a> {
-        let mut meta = MetaData::default();
-        let level = match self.level_parent_index() {
-            Some(l) => l,
-            None => return MetaData::default(),
-        } + 1;
-        macro_rules! get_field {
-            ($cell:ident) => {{
-                let mut size = None;
-                for i in (0..level).rev() {
-                    if let Some(cell_size) = &self.stack[i].$cell {
-                        size = Some(cell_size.clone());
-                        break;
-                    }
-                }
-                meta.$cell = size;
-            }};
+impl<'a, const DEPTH: usize> FdtIter<'a, DEPTH> {
+    /// Walk the whole tree, starting from the root node.
+    pub(crate) fn new(fdt: &'a Fdt<'a>) -> Self {
+        Self::subtree(fdt, 0, 1, MetaData::default())
+    }
+
+    /// Walk the subtree of the node whose `BEGIN_NODE` token is at struct
+    /// block `offset`, the node itself being yielded first.
+    pub(crate) fn subtree(
+        fdt: &'a Fdt<'a>,
+        offset: usize,
+        level: usize,
+        meta_parents: MetaData,
+    ) -> Self {
+        let reader = fdt.struct_reader(offset);
+        FdtIter {
+            fdt,
+            base_level: level.saturating_sub(1),
+            current_level: level.saturating_sub(1),
+            reader,
+            meta_base: meta_parents,
+            stack: core::array::from_fn(|_| MetaData::default()),
+            node_reader: None,
+            node_name: "",
+            node_offset: offset,
+            done: false,
+            error: None,
         }
+    }
 
-        get_field!(address_cells);
-        get_field!(size_cells);
-        get_field!(clock_cells);
-        get_field!(interrupt_cells);
-        get_field!(gpio_cells);
-        get_field!(dma_cells);
-        get_field!(cooling_cells);
-        get_field!(range);
-        get_field!(interrupt_parent);
+    /// Struct block offset of the next token to be read.
+    pub(crate) fn offset(&self) -> usize {
+        self.fdt.struct_offset(&self.reader)
+    }
 
-        meta
+    fn get_meta_parent(&self) -> MetaData {
+        let level = self.level_current_index().unwrap_or_default();
+        self.stack
+            .iter()
+            .take(level)
+            .fold(self.meta_base.clone(), |meta, own| own.merge(&meta))
     }
-    fn level_current_index(&self) -> usize {
-        self.current_level - 1
+
+    fn level_current_index(&self) -> Option<usize> {
+        self.current_level
+            .checked_sub(self.base_level.checked_add(1)?)
     }
-    fn level_parent_index(&self) -> Option<usize> {
-        if self.level_current_index() > 0 {
-            Some(self.level_current_index() - 1)
-        } else {
-            None
-        }
+
+    /// Metadata declared by the node being read.
+    fn current_meta(&mut self) -> Option<&mut MetaData> {
+        let i = self.level_current_index()?;
+        self.stack.get_mut(i)
     }
 
-    fn handle_node_begin(&mut self) {
-        self.current_level += 1;
-        let i = self.level_current_index();
-        self.stack[i] = MetaData::default();
-        self.node_name = self.reader.take_unit_name().unwrap();
+    fn handle_node_begin(&mut self) -> FdtResult<'a> {
+        self.node_offset = self.offset().saturating_sub(size_of::<u32>());
+        self.current_level = self.current_level.checked_add(1).ok_or(FdtError::TooDeep)?;
+        *self.current_meta().ok_or(FdtError::TooDeep)? = MetaData::default();
+        self.node_name = self.reader.take_unit_name()?;
         self.node_reader = Some(self.reader.clone());
+        Ok(())
     }
 
     fn finish_node(&mut self) -> Option<Node<'a>> {
         let reader = self.node_reader.take()?;
         let level = self.current_level;
-        let meta = self.stack[self.level_current_index()].clone();
+        let meta = self.current_meta()?.clone();
         let meta_parent = self.get_meta_parent();
 
-        let mut node = Node::new(self.fdt, level, self.node_name, reader, meta_parent, meta);
-        let ranges = node.node_ranges();
-        self.stack[self.level_current_index()].range = ranges.clone();
-        let ph = node.node_interrupt_parent();
-        self.stack[self.level_current_index()].interrupt_parent = ph;
+        let mut node = Node::new(
+            self.fdt,
+            level,
+            self.node_name,
+            self.node_offset,
+            reader,
+            meta_parent,
+            meta,
+        );
+        let current = self.current_meta()?;
+        current.interrupt_parent = node.node_interrupt_parent();
 
-        node.meta = self.stack[self.level_current_index()].clone();
+        node.meta = current.clone();
 
         Some(node)
     }
 }
 
-impl<'a> Iterator for FdtIter<'a> {
-    type Item = Node<'a>;
+impl<'a, const DEPTH: usize> Iterator for FdtIter<'a, DEPTH> {
+    type Item = FdtResult<'a, Node<'a>>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
+            if self.done {
+                return self.error.take().map(Err);
+            }
             let token = self.reader.take_token()?;
 
             match token {
                 Token::BeginNode => {
                     let node = self.finish_node();
-                    self.handle_node_begin();
-                    if node.is_some() {
-                        return node;
+                    if let Err(e) = self.handle_node_begin() {
+                        self.done = true;
+                        self.error = Some(e);
+                    }
+                    if let Some(node) = node {
+                        return Some(Ok(node));
                     }
                 }
                 Token::EndNode => {
                     let node = self.finish_node();
-                    self.current_level -= 1;
-                    if node.is_some() {
-                        return node;
+                    if self.current_level == self.base_level {
+                        self.done = true;
+                        self.error = Some(FdtError::UnbalancedNodes);
+                        return node.map(Ok);
+                    }
+                    self.current_level = self.current_level.saturating_sub(1);
+                    if self.current_level == self.base_level {
+                        self.done = true;
+                    }
+                    if let Some(node) = node {
+                        return Some(Ok(node));
                     }
                 }
                 Token::Prop => {
                     let prop = self.reader.take_prop(self.fdt)?;
-                    let index = self.level_current_index();
+                    let current = match self.current_meta() {
+                        Some(current) => current,
+                        None => continue,
+                    };
                     macro_rules! update_cell {
                         ($cell:ident) => {
-                            self.stack[index].$cell = Some(prop.u32() as _)
+                            current.$cell = prop.try_u32().ok().map(|v| v as _)
                         };
                     }
                     match prop.name {
@@ -275,7 +521,7 @@ impl<'a> Iterator for FdtIter<'a> {
                     }
                 }
                 Token::End => {
-                    return self.finish_node();
+                    return self.finish_node().map(Ok);
                 }
                 _ => {}
             }
@@ -283,54 +529,21 @@ impl<'a> Iterator for FdtIter<'a> {
     }
 }
 
-struct IterFindNode<'a> {
-    itr: FdtIter<'a>,
-    want: &'a str,
-    want_itr: usize,
-    is_path_last: bool,
-}
-
-impl<'a> IterFindNode<'a> {
-    fn new(itr: FdtIter<'a>, want: &'a str) -> Self {
-        IterFindNode {
-            itr,
-            want,
-            want_itr: 0,
-            is_path_last: false,
-        }
+/// Match a node name against one component of a path, the unit address is
+/// only compared when `want` has one.
+pub(crate) fn name_matches(name: &str, want: &str) -> bool {
+    if want.contains("@") {
+        name.eq(want)
+    } else {
+        let name = name.split("@").next().unwrap_or(name);
+        name.eq(want)
     }
 }
 
-impl<'a> Iterator for IterFindNode<'a> {
-    type Item = Node<'a>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut out = None;
-        loop {
-            let mut parts = self.want.split("/").filter(|o| !o.is_empty());
-            let mut want_part = "/";
-            for _ in 0..self.want_itr {
-                if let Some(part) = parts.next() {
-                    want_part = part;
-                } else {
-                    self.is_path_last = true;
-                    if let Some(out) = out {
-                        return Some(out);
-                    }
-                }
-            }
-            let node = self.itr.next()?;
-
-            let eq = if want_part.contains("@") {
-                node.name.eq(want_part)
-            } else {
-                let name = node.name.split("@").next().unwrap();
-                name.eq(want_part)
-            };
-            if eq {
-                self.want_itr += 1;
-                out = Some(node);
-            }
-        }
-    }
+/// Split an absolute path into the components leading to the parent of the
+/// node it names, and the name of that node, empty for the root node.
+pub(crate) fn split_path(path: &str) -> (impl Iterator<Item = &str>, &str) {
+    let path = path.trim_end_matches('/');
+    let (dir, last) = path.rsplit_once('/').unwrap_or(("", path));
+    (dir.split('/').filter(|p| !p.is_empty()), last)
 }
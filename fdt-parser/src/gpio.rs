@@ -0,0 +1,248 @@
+use crate::{
+    error::*,
+    node::Node,
+    phandle::PhandleArgs,
+    value::{Cells, StrList},
+    Specifier,
+};
+
+/// Flags cell of a GPIO specifier, the `GPIO_*` constants of
+/// `dt-bindings/gpio/gpio.h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GpioFlags(pub u32);
+
+impl GpioFlags {
+    pub const ACTIVE_LOW: u32 = 1 << 0;
+    pub const SINGLE_ENDED: u32 = 1 << 1;
+    pub const LINE_OPEN_DRAIN: u32 = 1 << 2;
+    pub const TRANSITORY: u32 = 1 << 3;
+    pub const PULL_UP: u32 = 1 << 4;
+    pub const PULL_DOWN: u32 = 1 << 5;
+    pub const PULL_DISABLE: u32 = 1 << 6;
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn is_active_low(&self) -> bool {
+        self.0 & Self::ACTIVE_LOW != 0
+    }
+
+    /// The line is only driven low, `GPIO_OPEN_DRAIN`.
+    pub fn is_open_drain(&self) -> bool {
+        self.0 & Self::SINGLE_ENDED != 0 && self.0 & Self::LINE_OPEN_DRAIN != 0
+    }
+
+    /// The line is only driven high, `GPIO_OPEN_SOURCE`.
+    pub fn is_open_source(&self) -> bool {
+        self.0 & Self::SINGLE_ENDED != 0 && self.0 & Self::LINE_OPEN_DRAIN == 0
+    }
+
+    /// The line state may be lost when the consumer sleeps.
+    pub fn is_transitory(&self) -> bool {
+        self.0 & Self::TRANSITORY != 0
+    }
+
+    pub fn is_pull_up(&self) -> bool {
+        self.0 & Self::PULL_UP != 0
+    }
+
+    pub fn is_pull_down(&self) -> bool {
+        self.0 & Self::PULL_DOWN != 0
+    }
+
+    pub fn is_pull_disable(&self) -> bool {
+        self.0 & Self::PULL_DISABLE != 0
+    }
+}
+
+/// One GPIO of a consumer, see [Node::gpios].
+#[derive(Clone)]
+pub struct GpioSpec<'a> {
+    /// The `gpio-controller` the line belongs to, past any `gpio-map`.
+    pub controller: Node<'a>,
+    /// Line in the bank for controllers with 3 `#gpio-cells`, the first cell
+    /// for layouts of the controller's own.
+    pub line: u32,
+    /// 0 for controllers with a single `#gpio-cells`, or a layout of their
+    /// own.
+    pub flags: GpioFlags,
+    /// The specifier as is, in the format of the controller's `#gpio-cells`.
+    pub specifier: Specifier,
+}
+
+impl<'a> TryFrom<PhandleArgs<'a>> for GpioSpec<'a> {
+    type Error = FdtError<'a>;
+
+    fn try_from(args: PhandleArgs<'a>) -> Result<Self, Self::Error> {
+        let (line, flags) = line_and_flags(&args.args).ok_or(FdtError::BadCellSize(0))?;
+        Ok(GpioSpec {
+            line,
+            flags,
+            specifier: args.args,
+            controller: args.provider,
+        })
+    }
+}
+
+/// Line and flags of a GPIO specifier: `<line>`, `<line flags>`, or
+/// `<bank line flags>` as used by controllers with 3 `#gpio-cells`. Only the
+/// line is taken from longer, controller specific, layouts.
+fn line_and_flags(cells: &[u32]) -> Option<(u32, GpioFlags)> {
+    match *cells {
+        [] => None,
+        [line] => Some((line, GpioFlags::default())),
+        [line, flags] | [_, line, flags] => Some((line, GpioFlags(flags))),
+        [line, ..] => Some((line, GpioFlags::default())),
+    }
+}
+
+/// Lines of a GPIO controller backed by pins of a pin controller, one entry
+/// of `gpio-ranges`.
+#[derive(Clone)]
+pub struct GpioRange<'a> {
+    pub pinctrl: Node<'a>,
+    pub gpio_offset: u32,
+    pub pin_offset: u32,
+    /// 0 when the pins are named by `gpio-ranges-group-names` instead.
+    pub count: u32,
+}
+
+impl GpioRange<'_> {
+    /// The pin of the pin controller behind GPIO `line`.
+    pub fn pin_of(&self, line: u32) -> Option<u32> {
+        let offset = line.checked_sub(self.gpio_offset)?;
+        (offset < self.count).then_some(self.pin_offset.checked_add(offset)?)
+    }
+}
+
+/// Value a `gpio-hog` sets its lines to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HogState {
+    Input,
+    OutputLow,
+    OutputHigh,
+}
+
+/// A `gpio-hog` child of a GPIO controller, lines the controller sets up by
+/// itself at probe, see [Node::gpio_hogs].
+#[derive(Clone)]
+pub struct GpioHog<'a> {
+    pub node: Node<'a>,
+    pub state: HogState,
+    /// `line-name`, else the name of the node.
+    pub line_name: &'a str,
+    gpios: Cells<'a>,
+    gpio_cells: usize,
+}
+
+impl GpioHog<'_> {
+    /// Line and flags of each hogged GPIO.
+    pub fn lines(&self) -> impl Iterator<Item = (u32, GpioFlags)> + '_ {
+        let cells = self.gpio_cells.max(1);
+        (0..self.gpios.len().checked_div(cells).unwrap_or_default()).filter_map(move |i| {
+            let mut spec = [0u32; Specifier::MAX_CELLS];
+            let spec = spec.get_mut(..cells)?;
+            let gpio = self.gpios.iter().skip(i.checked_mul(cells)?);
+            for (out, cell) in spec.iter_mut().zip(gpio) {
+                *out = cell;
+            }
+            line_and_flags(spec)
+        })
+    }
+}
+
+impl<'a> Node<'a> {
+    /// GPIOs of this consumer from `<name>-gpios`, or from `gpios` when
+    /// `name` is `None`, also accepting the deprecated `<name>-gpio` and
+    /// `gpio` forms. Follows `gpio-map` nexus nodes to the controller.
+    pub fn gpios(
+        &self,
+        name: Option<&str>,
+    ) -> impl Iterator<Item = FdtResult<'a, GpioSpec<'a>>> + 'a {
+        let is_list = |prop_name: &str| match name {
+            Some(name) => ["-gpios", "-gpio"]
+                .iter()
+                .any(|suffix| prop_name.strip_suffix(suffix) == Some(name)),
+            None => prop_name == "gpios" || prop_name == "gpio",
+        };
+        let list = self.propertys().find(|p| is_list(p.name)).map(|p| p.name);
+        let node = self.clone();
+
+        list.into_iter().flat_map(move |list| {
+            node.parse_phandle_with_args_map(list, "gpio")
+                .map(|args| args.and_then(GpioSpec::try_from))
+        })
+    }
+
+    /// Entries of the `gpio-ranges` of this GPIO controller.
+    pub fn gpio_ranges(&self) -> impl Iterator<Item = FdtResult<'a, GpioRange<'a>>> + 'a {
+        self.parse_phandle_with_fixed_args("gpio-ranges", 3)
+            .map(|range| {
+                let range = range?;
+                let cell = |i| range.args.get(i).copied().ok_or(FdtError::BadCellSize(i));
+                Ok(GpioRange {
+                    gpio_offset: cell(0)?,
+                    pin_offset: cell(1)?,
+                    count: cell(2)?,
+                    pinctrl: range.provider,
+                })
+            })
+    }
+
+    /// Name of `line` in the `gpio-line-names` of this GPIO controller,
+    /// `None` if it has none or it is empty.
+    pub fn gpio_line_name(&self, line: u32) -> Option<&'a str> {
+        self.gpio_line_names()?
+            .iter()
+            .nth(line as usize)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// The line named `name` in the `gpio-line-names` of this GPIO
+    /// controller.
+    pub fn gpio_line_by_name(&self, name: &str) -> Option<u32> {
+        let line = self.gpio_line_names()?.iter().position(|n| n == name)?;
+        u32::try_from(line).ok()
+    }
+
+    fn gpio_line_names(&self) -> Option<StrList<'a>> {
+        let prop = self.find_property("gpio-line-names")?;
+        Some(StrList::new(prop.raw_value()))
+    }
+
+    /// `gpio-hog` children of this GPIO controller. A hog without `gpios`
+    /// or without a state yields an error.
+    pub fn gpio_hogs(&self) -> impl Iterator<Item = FdtResult<'a, GpioHog<'a>>> + 'a {
+        let gpio_cells = self.meta.gpio_cells.unwrap_or(2) as usize;
+
+        self.children()
+            .filter(|child| child.find_property("gpio-hog").is_some())
+            .map(move |node| {
+                let gpios = node
+                    .find_property("gpios")
+                    .ok_or(FdtError::NotFound("gpios"))?;
+                let state = if node.find_property("input").is_some() {
+                    HogState::Input
+                } else if node.find_property("output-low").is_some() {
+                    HogState::OutputLow
+                } else if node.find_property("output-high").is_some() {
+                    HogState::OutputHigh
+                } else {
+                    return Err(FdtError::NotFound("gpio-hog state"));
+                };
+                let line_name = match node.find_property("line-name") {
+                    Some(prop) => prop.try_str()?,
+                    None => node.name,
+                };
+
+                Ok(GpioHog {
+                    state,
+                    line_name,
+                    gpios: Cells::new(gpios.raw_value()),
+                    gpio_cells,
+                    node,
+                })
+            })
+    }
+}
@@ -0,0 +1,504 @@
+use core::{iter, ops::Deref};
+
+use crate::{
+    error::*, fdt::split_path, property::Property, read::FdtReader, Fdt, NodeOffset, Phandle, Token,
+};
+
+const NONE: u32 = u32::MAX;
+
+/// One node, or one `compatible` string of a node, of a [FdtIndex].
+#[derive(Debug, Clone, Copy)]
+pub struct IndexEntry {
+    offset: u32,
+    level: u32,
+    parent: u32,
+    first_child: u32,
+    next_sibling: u32,
+    phandle: u32,
+    /// Index of the entry holding the n-th smallest phandle.
+    by_phandle: u32,
+    /// The `compatible` list of a node, or the single string of a
+    /// compatible entry, in the structure block.
+    compatible_offset: u32,
+    compatible_len: u32,
+}
+
+impl Default for IndexEntry {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            level: 0,
+            parent: NONE,
+            first_child: NONE,
+            next_sibling: NONE,
+            phandle: 0,
+            by_phandle: 0,
+            compatible_offset: 0,
+            compatible_len: 0,
+        }
+    }
+}
+
+impl IndexEntry {
+    fn node_offset(&self) -> NodeOffset {
+        NodeOffset(self.offset as _)
+    }
+}
+
+enum Entries<'i> {
+    Borrowed(&'i [IndexEntry]),
+    #[cfg(feature = "alloc")]
+    Owned(alloc::vec::Vec<IndexEntry>),
+}
+
+impl Deref for Entries<'_> {
+    type Target = [IndexEntry];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Entries::Borrowed(e) => e,
+            #[cfg(feature = "alloc")]
+            Entries::Owned(e) => e,
+        }
+    }
+}
+
+/// Lookup tables built once over a blob, mapping phandles, paths and
+/// compatibles to nodes.
+///
+/// Attach it with [Fdt::with_index] and [Fdt::get_node_by_phandle],
+/// [Fdt::find_nodes], [Fdt::find_compatible] and [Fdt::node_at] use it instead
+/// of walking the structure block.
+pub struct FdtIndex<'a> {
+    struct_bytes: &'a [u8],
+    /// One entry per node in tree order, then one per `compatible` string
+    /// sorted by string, whose `parent` is the node listing it.
+    entries: Entries<'a>,
+    nodes: usize,
+}
+
+impl<'a> FdtIndex<'a> {
+    /// Number of [IndexEntry] needed to index `fdt`, one per node and one
+    /// per string of their `compatible` properties.
+    pub fn required_len(fdt: &Fdt<'a>) -> usize {
+        walk(fdt)
+            .map_while(Result::ok)
+            .map(|event| match event {
+                Walk::Node { .. } => 1,
+                Walk::Prop(prop) if prop.name == "compatible" => {
+                    compatible_strings(prop.raw_value(), 0).count()
+                }
+                Walk::Prop(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Build the index in a caller provided buffer of at least
+    /// [FdtIndex::required_len] entries.
+    pub fn new_in(fdt: &Fdt<'a>, buffer: &'a mut [IndexEntry]) -> FdtResult<'a, Self> {
+        let (nodes, len) = Self::build(fdt, buffer)?;
+        let entries: &'a [IndexEntry] = buffer;
+        Ok(Self {
+            struct_bytes: fdt.struct_bytes(),
+            entries: Entries::Borrowed(entries.get(..len).unwrap_or_default()),
+            nodes,
+        })
+    }
+
+    /// Build the index in a newly allocated buffer.
+    #[cfg(feature = "alloc")]
+    pub fn new(fdt: &Fdt<'a>) -> FdtResult<'a, Self> {
+        let mut buffer = alloc::vec![IndexEntry::default(); Self::required_len(fdt)];
+        let (nodes, len) = Self::build(fdt, &mut buffer)?;
+        buffer.truncate(len);
+        Ok(Self {
+            struct_bytes: fdt.struct_bytes(),
+            entries: Entries::Owned(buffer),
+            nodes,
+        })
+    }
+
+    /// Fill `buffer`, returning the number of nodes and of entries.
+    fn build(fdt: &Fdt<'a>, buffer: &mut [IndexEntry]) -> FdtResult<'a, (usize, usize)> {
+        let base = fdt.struct_bytes().as_ptr() as usize;
+        let mut len: usize = 0;
+
+        for event in walk(fdt) {
+            match event? {
+                Walk::Node { offset, level } => {
+                    let level = to_u32(level)?;
+                    let mut entry = IndexEntry {
+                        offset: to_u32(offset)?,
+                        level,
+                        by_phandle: to_u32(len)?,
+                        ..Default::default()
+                    };
+
+                    // The parent is the first node before this one that is
+                    // higher up, the last node of our level seen on the way is
+                    // the previous sibling.
+                    let mut prev = None;
+                    let mut parent = len.checked_sub(1);
+                    while let Some(p) = parent.and_then(|i| buffer.get(i)) {
+                        if p.level < level {
+                            break;
+                        }
+                        if p.level == level {
+                            prev = parent;
+                        }
+                        parent = (p.parent != NONE).then_some(p.parent as usize);
+                    }
+                    entry.parent = match parent {
+                        Some(parent) => to_u32(parent)?,
+                        None => NONE,
+                    };
+                    let link = match (prev, parent) {
+                        (Some(prev), _) => buffer.get_mut(prev).map(|e| &mut e.next_sibling),
+                        (None, Some(parent)) => buffer.get_mut(parent).map(|e| &mut e.first_child),
+                        _ => None,
+                    };
+                    if let Some(link) = link {
+                        *link = to_u32(len)?;
+                    }
+
+                    *buffer.get_mut(len).ok_or(FdtError::BufferTooSmall)? = entry;
+                    len = len.checked_add(1).ok_or(FdtError::BufferTooSmall)?;
+                }
+                Walk::Prop(prop) => {
+                    // Properties come before subnodes, so they belong to the
+                    // node begun last.
+                    let entry = match len.checked_sub(1).and_then(|i| buffer.get_mut(i)) {
+                        Some(entry) => entry,
+                        None => continue,
+                    };
+                    match prop.name {
+                        "phandle" => entry.phandle = prop.try_u32().unwrap_or_default(),
+                        // Older blobs only have this one, `phandle` wins when
+                        // both are present.
+                        "linux,phandle" if entry.phandle == 0 => {
+                            entry.phandle = prop.try_u32().unwrap_or_default()
+                        }
+                        "compatible" => {
+                            let value = prop.raw_value();
+                            let offset = (value.as_ptr() as usize)
+                                .checked_sub(base)
+                                .ok_or(FdtError::StructBlockOutOfBounds)?;
+                            entry.compatible_offset = to_u32(offset)?;
+                            entry.compatible_len = to_u32(value.len())?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        sort_by_phandle(buffer.get_mut(..len).unwrap_or_default());
+
+        let nodes = len;
+        for node in 0..nodes {
+            let Some(entry) = buffer.get(node).copied() else {
+                break;
+            };
+            let list = entry.compatible_offset as usize;
+            let value = compatible_str(fdt.struct_bytes(), &entry);
+            for (start, compatible) in compatible_strings(value, list) {
+                *buffer.get_mut(len).ok_or(FdtError::BufferTooSmall)? = IndexEntry {
+                    parent: to_u32(node)?,
+                    compatible_offset: to_u32(start)?,
+                    compatible_len: to_u32(compatible.len())?,
+                    ..Default::default()
+                };
+                len = len.checked_add(1).ok_or(FdtError::BufferTooSmall)?;
+            }
+        }
+        let struct_bytes = fdt.struct_bytes();
+        let key = |entries: &[IndexEntry], i: usize| {
+            entries
+                .get(i)
+                .map(|e| (compatible_str(struct_bytes, e), e.parent))
+        };
+        heapsort(
+            buffer.get_mut(nodes..len).unwrap_or_default(),
+            |entries, a, b| key(entries, a) < key(entries, b),
+            |entries, a, b| entries.swap(a, b),
+        );
+
+        Ok((nodes, len))
+    }
+
+    /// Number of nodes indexed.
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    /// Entries of the nodes, in tree order.
+    fn nodes(&self) -> &[IndexEntry] {
+        self.entries.get(..self.nodes).unwrap_or_default()
+    }
+
+    /// Entries of the `compatible` strings, sorted by string.
+    fn compatible_table(&self) -> &[IndexEntry] {
+        self.entries.get(self.nodes..).unwrap_or_default()
+    }
+
+    pub(crate) fn belongs_to(&self, fdt: &Fdt<'_>) -> bool {
+        core::ptr::eq(self.struct_bytes, fdt.struct_bytes())
+    }
+
+    fn entry(&self, i: u32) -> Option<&IndexEntry> {
+        self.nodes().get(i as usize)
+    }
+
+    fn position(&self, offset: usize) -> Option<u32> {
+        let i = self
+            .nodes()
+            .binary_search_by_key(&(offset as u32), |e| e.offset)
+            .ok()?;
+        Some(i as u32)
+    }
+
+    fn name(&self, entry: &IndexEntry) -> &'a str {
+        let struct_bytes = self.struct_bytes;
+        struct_bytes
+            .get((entry.offset as usize).saturating_add(size_of::<u32>())..)
+            .map(FdtReader::new)
+            .and_then(|mut r| r.take_unit_name().ok())
+            .unwrap_or_default()
+    }
+
+    fn children(&self, parent: u32) -> impl Iterator<Item = u32> + '_ {
+        let first = self.entry(parent).map(|e| e.first_child).unwrap_or(NONE);
+        core::iter::successors(Some(first), |&i| self.entry(i).map(|e| e.next_sibling))
+            .take_while(|&i| i != NONE)
+    }
+
+    /// The node with the given phandle.
+    pub fn find_phandle(&self, phandle: Phandle) -> Option<NodeOffset> {
+        let want = phandle.as_usize() as u32;
+        if want == 0 {
+            return None;
+        }
+        let (mut lo, mut hi) = (0, self.nodes);
+        while lo < hi {
+            let mid = lo.midpoint(hi);
+            let entry = self.entry(self.entry(u32::try_from(mid).ok()?)?.by_phandle)?;
+            match entry.phandle.cmp(&want) {
+                core::cmp::Ordering::Less => lo = mid.saturating_add(1),
+                core::cmp::Ordering::Greater => hi = mid,
+                core::cmp::Ordering::Equal => return Some(entry.node_offset()),
+            }
+        }
+        None
+    }
+
+    /// The node with the given absolute path, see [Fdt::find_nodes] for how
+    /// unit addresses are matched.
+    pub fn find_path(&self, path: &str) -> Option<NodeOffset> {
+        self.find_path_all(path).next()
+    }
+
+    /// Every node the last component of `path` matches under its parent.
+    pub(crate) fn find_path_all<'p>(
+        &'p self,
+        path: &'p str,
+    ) -> impl Iterator<Item = NodeOffset> + 'p {
+        let (mut dir, last) = split_path(path);
+        let root = (!self.is_empty()).then_some(0);
+        let parent = root.and_then(|root| {
+            dir.try_fold(root, |node, part| {
+                self.children(node).find(|&c| self.name_matches(c, part))
+            })
+        });
+
+        let matches = parent
+            .filter(|_| !last.is_empty())
+            .into_iter()
+            .flat_map(move |p| {
+                self.children(p)
+                    .filter(move |&c| self.name_matches(c, last))
+            });
+
+        root.filter(|_| last.is_empty())
+            .into_iter()
+            .chain(matches)
+            .filter_map(|i| self.entry(i))
+            .map(IndexEntry::node_offset)
+    }
+
+    fn name_matches(&self, i: u32, want: &str) -> bool {
+        self.entry(i)
+            .map(|e| crate::fdt::name_matches(self.name(e), want))
+            .unwrap_or_default()
+    }
+
+    /// Nodes listing `compatible` in their `compatible` property, in tree
+    /// order.
+    pub fn find_compatible<'p>(
+        &'p self,
+        compatible: &'p str,
+    ) -> impl Iterator<Item = NodeOffset> + 'p {
+        let mut after = None;
+        iter::from_fn(move || {
+            let node = self.next_compatible(compatible, after)?;
+            after = Some(node);
+            self.entry(node).map(IndexEntry::node_offset)
+        })
+    }
+
+    /// The first node after the node `after` in tree order listing
+    /// `compatible`, found by binary search of the compatible entries.
+    pub(crate) fn next_compatible(&self, compatible: &str, after: Option<u32>) -> Option<u32> {
+        let table = self.compatible_table();
+        let want = (compatible.as_bytes(), after);
+        let i = table
+            .partition_point(|e| (compatible_str(self.struct_bytes, e), Some(e.parent)) <= want);
+        let entry = table.get(i)?;
+        (compatible_str(self.struct_bytes, entry) == compatible.as_bytes()).then_some(entry.parent)
+    }
+
+    pub(crate) fn entry_offset(&self, i: u32) -> Option<NodeOffset> {
+        self.entry(i).map(IndexEntry::node_offset)
+    }
+
+    /// The ancestor of the node at `target` whose parent is the node at
+    /// `from`.
+    pub(crate) fn child_towards(&self, from: usize, target: usize) -> Option<usize> {
+        let from = self.position(from)?;
+        let mut i = self.position(target)?;
+        loop {
+            let entry = self.entry(i)?;
+            if entry.parent == from {
+                return Some(entry.offset as _);
+            }
+            i = entry.parent;
+        }
+    }
+}
+
+enum Walk<'a> {
+    Node { offset: usize, level: usize },
+    Prop(Property<'a>),
+}
+
+/// Walk the structure block token by token. Unlike [Fdt::all_nodes] there is
+/// no nesting limit.
+fn walk<'a, 'b>(fdt: &'b Fdt<'a>) -> impl Iterator<Item = FdtResult<'a, Walk<'a>>> + 'b {
+    let mut reader = fdt.struct_reader(0);
+    let mut level: usize = 0;
+    iter::from_fn(move || loop {
+        match reader.take_token()? {
+            Token::BeginNode => {
+                let offset = fdt.struct_offset(&reader).saturating_sub(size_of::<u32>());
+                level = match level.checked_add(1) {
+                    Some(level) => level,
+                    None => return Some(Err(FdtError::TooDeep)),
+                };
+                return Some(
+                    reader
+                        .take_unit_name()
+                        .map(|_| Walk::Node { offset, level }),
+                );
+            }
+            Token::EndNode => {
+                level = match level.checked_sub(1) {
+                    Some(level) => level,
+                    None => return Some(Err(FdtError::UnbalancedNodes)),
+                };
+            }
+            Token::Prop => return Some(reader.take_prop(fdt).map(Walk::Prop).ok_or(FdtError::Eof)),
+            Token::End => return None,
+            _ => {}
+        }
+    })
+}
+
+/// Heapsort of the `by_phandle` permutation, ordering by phandle.
+fn sort_by_phandle(entries: &mut [IndexEntry]) {
+    let key = |entries: &[IndexEntry], i: usize| {
+        entries
+            .get(i)
+            .and_then(|e| entries.get(e.by_phandle as usize))
+            .map(|e| e.phandle)
+            .unwrap_or_default()
+    };
+    let swap = |entries: &mut [IndexEntry], a: usize, b: usize| {
+        let (Some(ea), Some(eb)) = (entries.get(a), entries.get(b)) else {
+            return;
+        };
+        let (pa, pb) = (ea.by_phandle, eb.by_phandle);
+        if let Some(e) = entries.get_mut(a) {
+            e.by_phandle = pb;
+        }
+        if let Some(e) = entries.get_mut(b) {
+            e.by_phandle = pa;
+        }
+    };
+    heapsort(
+        entries,
+        |entries, a, b| key(entries, a) < key(entries, b),
+        swap,
+    );
+}
+
+/// Strings of a `compatible` list found at struct block offset `base`, with
+/// the offset of each.
+fn compatible_strings(value: &[u8], base: usize) -> impl Iterator<Item = (usize, &[u8])> {
+    let mut start = base;
+    value.split(|b| *b == 0).filter_map(move |s| {
+        let at = start;
+        start = start.saturating_add(s.len()).saturating_add(1);
+        (!s.is_empty()).then_some((at, s))
+    })
+}
+
+/// The string of a compatible entry.
+fn compatible_str<'a>(struct_bytes: &'a [u8], entry: &IndexEntry) -> &'a [u8] {
+    let start = entry.compatible_offset as usize;
+    struct_bytes
+        .get(start..start.saturating_add(entry.compatible_len as usize))
+        .unwrap_or_default()
+}
+
+/// Heapsort of `entries`, `less` comparing and `swap` exchanging the entries
+/// at two positions.
+fn heapsort(
+    entries: &mut [IndexEntry],
+    less: impl Fn(&[IndexEntry], usize, usize) -> bool,
+    swap: impl Fn(&mut [IndexEntry], usize, usize),
+) {
+    let sift_down = |entries: &mut [IndexEntry], mut root: usize, end: usize| {
+        while let Some(mut child) = root.checked_mul(2).and_then(|c| c.checked_add(1)) {
+            if child >= end {
+                break;
+            }
+            let right = child.saturating_add(1);
+            if right < end && less(entries, child, right) {
+                child = right;
+            }
+            if !less(entries, root, child) {
+                break;
+            }
+            swap(entries, root, child);
+            root = child;
+        }
+    };
+
+    let len = entries.len();
+    for start in (0..len / 2).rev() {
+        sift_down(entries, start, len);
+    }
+    for end in (1..len).rev() {
+        swap(entries, 0, end);
+        sift_down(entries, 0, end);
+    }
+}
+
+/// `value` as an index field, failing for blobs beyond what the header can
+/// describe.
+fn to_u32<'a>(value: usize) -> FdtResult<'a, u32> {
+    u32::try_from(value).map_err(|_| FdtError::StructBlockOutOfBounds)
+}
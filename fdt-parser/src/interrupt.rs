@@ -1,11 +1,516 @@
-use crate::node::Node;
+use core::iter;
 
+use crate::{
+    error::*,
+    fdt::DEFAULT_DEPTH,
+    node::Node,
+    read::FdtReader,
+    value::{Cells, StrList},
+    Fdt, Phandle, Specifier,
+};
+
+#[derive(Clone)]
 pub struct InterruptController<'a> {
     pub node: Node<'a>,
 }
 
-impl InterruptController<'_> {
+impl<'a> InterruptController<'a> {
+    pub fn try_interrupt_cells(&self) -> FdtResult<'a, usize> {
+        let prop = self
+            .node
+            .find_property("#interrupt-cells")
+            .ok_or(FdtError::NotFound("#interrupt-cells"))?;
+        Ok(prop.try_u32()? as _)
+    }
+
+    /// # Panics
+    ///
+    /// If `#interrupt-cells` is missing, see
+    /// [InterruptController::try_interrupt_cells].
+    #[allow(clippy::expect_used)]
     pub fn interrupt_cells(&self) -> usize {
-        self.node.find_property("#interrupt-cells").unwrap().u32() as _
+        self.try_interrupt_cells()
+            .expect("#interrupt-cells not found")
+    }
+
+    /// The controller this one signals its interrupts to. `None` for a root
+    /// controller, including one whose inherited `interrupt-parent` is
+    /// itself, as is usual for the GIC.
+    pub fn parent(&self) -> Option<InterruptController<'a>> {
+        self.node
+            .interrupt_parent()
+            .filter(|parent| parent.node.offset != self.node.offset)
+    }
+
+    /// An `interrupt-controller` without a parent, the end of a cascade.
+    pub fn is_root(&self) -> bool {
+        self.node.find_property("interrupt-controller").is_some() && self.parent().is_none()
+    }
+
+    /// Controllers up the cascade from the parent of this one to the root.
+    /// Ends with [FdtError::InterruptParentLoop] when `interrupt-parent`
+    /// links form a cycle.
+    pub fn cascade(&self) -> CascadeIter<'a> {
+        let mut seen = [0; DEFAULT_DEPTH];
+        if let Some(first) = seen.first_mut() {
+            *first = self.node.offset;
+        }
+
+        CascadeIter {
+            current: Some(self.clone()),
+            seen,
+            len: 1,
+        }
+    }
+
+    /// The root controller this one cascades to, itself if it is a root.
+    pub fn root(&self) -> FdtResult<'a, InterruptController<'a>> {
+        self.cascade().try_fold(self.clone(), |_, parent| parent)
+    }
+
+    /// Number of controllers between this one and the root.
+    pub fn depth(&self) -> FdtResult<'a, usize> {
+        self.cascade().try_fold(0, |depth: usize, parent| {
+            parent.map(|_| depth.saturating_add(1))
+        })
+    }
+
+    /// Kind of controller, from its `compatible`, or else from its
+    /// `gpio-controller` and `msi-controller` properties.
+    pub fn kind(&self) -> InterruptControllerKind {
+        use InterruptControllerKind::*;
+
+        for c in self.node.compatibles() {
+            let kind = match c {
+                "arm,gic-v3-its" | "arm,gic-v2m-frame" => Msi,
+                _ if c.starts_with("arm,gic-v3") => ArmGicV3,
+                "arm,arm11mp-gic" | "qcom,msm-qgic2" => ArmGic,
+                _ if c.starts_with("arm,gic") => ArmGic,
+                _ if c.starts_with("arm,cortex-a") && c.ends_with("-gic") => ArmGic,
+                "brcm,bcm2835-armctrl-ic" | "brcm,bcm2836-armctrl-ic" => Bcm2835ArmCtrl,
+                "brcm,bcm2836-l1-intc" => Bcm2836Local,
+                "brcm,l2-intc" => BrcmL2,
+                "riscv,aplic" => RiscvAplic,
+                "riscv,cpu-intc" => RiscvCpu,
+                _ if c.ends_with("plic0") || c.contains(",plic") || c.ends_with("-plic") => {
+                    RiscvPlic
+                }
+                _ => continue,
+            };
+            return kind;
+        }
+        if self.node.find_property("gpio-controller").is_some() {
+            Gpio
+        } else if self.node.find_property("msi-controller").is_some() {
+            Msi
+        } else {
+            Other
+        }
+    }
+}
+
+/// Family of an [InterruptController], see [InterruptController::kind].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptControllerKind {
+    /// ARM GIC v1 and v2, such as the GIC-400.
+    ArmGic,
+    ArmGicV3,
+    /// BCM2835 ARM interrupt controller, and its BCM2836 variant.
+    Bcm2835ArmCtrl,
+    /// BCM2836 per-core local interrupt controller.
+    Bcm2836Local,
+    /// Broadcom second level interrupt controller.
+    BrcmL2,
+    RiscvPlic,
+    RiscvAplic,
+    /// Per-hart RISC-V local interrupt controller.
+    RiscvCpu,
+    /// GPIO controller that is also an interrupt controller.
+    Gpio,
+    /// MSI controller, such as a GICv3 ITS.
+    Msi,
+    Other,
+}
+
+/// Iterator up an interrupt controller cascade, see
+/// [InterruptController::cascade].
+pub struct CascadeIter<'a> {
+    current: Option<InterruptController<'a>>,
+    /// Offsets of the controllers visited so far.
+    seen: [usize; DEFAULT_DEPTH],
+    len: usize,
+}
+
+impl<'a> Iterator for CascadeIter<'a> {
+    type Item = FdtResult<'a, InterruptController<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parent = self.current.take()?.parent()?;
+        let seen = self.seen.get(..self.len).unwrap_or_default();
+        if seen.contains(&parent.node.offset) {
+            return Some(Err(FdtError::InterruptParentLoop));
+        }
+        let Some(slot) = self.seen.get_mut(self.len) else {
+            return Some(Err(FdtError::TooDeep));
+        };
+        *slot = parent.node.offset;
+        self.len = self.len.saturating_add(1);
+        self.current = Some(parent.clone());
+        Some(Ok(parent))
+    }
+}
+
+impl<'a> Fdt<'a> {
+    /// Nodes marked `interrupt-controller` in tree order, except that each
+    /// one comes after the controllers it cascades to: an order in which to
+    /// initialize them, found in a single walk of the tree.
+    ///
+    /// A controller that can't be ordered yields an error in its place:
+    /// [FdtError::InterruptParentLoop] when its cascade runs into a cycle,
+    /// see [InterruptController::cascade], and [FdtError::TooDeep] when more
+    /// than [DEFAULT_DEPTH] controllers from further down the tree would
+    /// have to come ahead of their place. Ends with the error of a walk that
+    /// can't go on, see [Fdt::all_nodes].
+    pub fn interrupt_controllers(
+        &'a self,
+    ) -> impl Iterator<Item = FdtResult<'a, InterruptController<'a>>> + 'a {
+        let mut nodes = self.all_nodes();
+        // Offsets of the controllers yielded ahead of their place in the tree.
+        let mut ahead = [0usize; DEFAULT_DEPTH];
+        let mut ahead_len: usize = 0;
+        // A controller and the ones it cascades to, yielded from the end.
+        let mut queue: [Option<InterruptController<'a>>; DEFAULT_DEPTH] = Default::default();
+        let mut queued: usize = 0;
+
+        iter::from_fn(move || loop {
+            if let Some(last) = queued.checked_sub(1) {
+                queued = last;
+                if let Some(controller) = queue.get_mut(last).and_then(Option::take) {
+                    return Some(Ok(controller));
+                }
+                continue;
+            }
+
+            let node = match nodes.next()? {
+                Ok(node) => node,
+                Err(e) => return Some(Err(e)),
+            };
+            if node.find_property("interrupt-controller").is_none() {
+                continue;
+            }
+            let offset = node.offset;
+            let yielded = ahead.get(..ahead_len).unwrap_or_default();
+            if let Some(i) = yielded.iter().position(|&o| o == offset) {
+                ahead_len = ahead_len.saturating_sub(1);
+                ahead.swap(i, ahead_len);
+                continue;
+            }
+
+            let controller = InterruptController { node };
+            let mut len: usize = 0;
+            let mut later = true;
+            for parent in controller.cascade() {
+                let parent = match parent {
+                    Ok(parent) => parent,
+                    Err(e) => return Some(Err(e)),
+                };
+                let o = parent.node.offset;
+                let yielded = ahead.get(..ahead_len).unwrap_or_default();
+                // Controllers before this one in the tree, and the ones they
+                // cascade to, are out already.
+                later = later && o > offset && !yielded.contains(&o);
+                if !later {
+                    continue;
+                }
+                let Some(slot) = queue.get_mut(len.saturating_add(1)) else {
+                    return Some(Err(FdtError::TooDeep));
+                };
+                *slot = Some(parent);
+                len = len.saturating_add(1);
+            }
+            if ahead_len.saturating_add(len) > ahead.len() {
+                return Some(Err(FdtError::TooDeep));
+            }
+            for parent in queue.iter().skip(1).take(len).flatten() {
+                if let Some(slot) = ahead.get_mut(ahead_len) {
+                    *slot = parent.node.offset;
+                    ahead_len = ahead_len.saturating_add(1);
+                }
+            }
+            if let Some(first) = queue.first_mut() {
+                *first = Some(controller);
+            }
+            queued = len.saturating_add(1);
+        })
+    }
+}
+
+/// An interrupt as the controller that handles it sees it, see
+/// [Node::map_interrupt].
+pub struct ResolvedInterrupt<'a> {
+    pub controller: InterruptController<'a>,
+    /// Interrupt specifier in the format of the controller's
+    /// `#interrupt-cells`.
+    pub specifier: Specifier,
+}
+
+impl<'a> Node<'a> {
+    /// Follow an interrupt from this interrupt parent to the controller
+    /// that handles it, like Linux `of_irq_parse_raw`.
+    ///
+    /// `unit_address` is the `reg` address of the device raising the
+    /// interrupt and `specifier` its interrupt specifier, in the format of
+    /// this node's `#interrupt-cells`. Nexus nodes on the way are crossed
+    /// through their `interrupt-map`, after masking with
+    /// `interrupt-map-mask`; unit address cells the device doesn't provide
+    /// are 0. Nodes that are neither a nexus nor an `interrupt-controller`
+    /// pass the interrupt to their own interrupt parent.
+    pub fn map_interrupt(
+        &self,
+        unit_address: &[u32],
+        specifier: &[u32],
+    ) -> FdtResult<'a, ResolvedInterrupt<'a>> {
+        let too_long = |cells: &[u32]| FdtError::BadCellSize(cells.len());
+        let mut node = self.clone();
+        let mut address = Specifier::new(unit_address).ok_or(too_long(unit_address))?;
+        let mut specifier = Specifier::new(specifier).ok_or(too_long(specifier))?;
+
+        for _ in 0..DEFAULT_DEPTH {
+            let interrupt_cells = node
+                .meta
+                .interrupt_cells
+                .ok_or(FdtError::NotFound("#interrupt-cells"))?;
+            if specifier.len() != interrupt_cells as usize {
+                return Err(FdtError::BadCellSize(specifier.len()));
+            }
+
+            if node.find_property("interrupt-map").is_some() {
+                (node, address, specifier) = node.interrupt_map_lookup(&address, &specifier)?;
+            } else if node.find_property("interrupt-controller").is_some() {
+                return Ok(ResolvedInterrupt {
+                    controller: InterruptController { node },
+                    specifier,
+                });
+            } else {
+                node = node
+                    .interrupt_parent()
+                    .ok_or(FdtError::NotFound("interrupt-parent"))?
+                    .node;
+            }
+        }
+
+        Err(FdtError::TooDeep)
+    }
+
+    /// The row of the `interrupt-map` of this nexus matching `address` and
+    /// `specifier`: its interrupt parent, and the unit address and specifier
+    /// on that parent.
+    fn interrupt_map_lookup(
+        &self,
+        address: &[u32],
+        specifier: &[u32],
+    ) -> FdtResult<'a, (Node<'a>, Specifier, Specifier)> {
+        let map = self
+            .find_property("interrupt-map")
+            .ok_or(FdtError::NotFound("interrupt-map"))?;
+        let address_cells = self.meta.address_cells.unwrap_or(2) as usize;
+        let key_len = address_cells
+            .checked_add(specifier.len())
+            .ok_or(FdtError::BadCellSize(address_cells))?;
+
+        let mut key = [0u32; Specifier::MAX_CELLS];
+        let key = key
+            .get_mut(..key_len)
+            .ok_or(FdtError::BadCellSize(key_len))?;
+        for (i, cell) in key.iter_mut().enumerate() {
+            *cell = match i.checked_sub(address_cells) {
+                None => address.get(i).copied().unwrap_or(0),
+                Some(i) => specifier.get(i).copied().unwrap_or(0),
+            };
+        }
+        let mut mask = [u32::MAX; Specifier::MAX_CELLS];
+        if let Some(prop) = self.find_property("interrupt-map-mask") {
+            let cells = Cells::new(prop.raw_value());
+            if cells.len() != key_len {
+                return Err(FdtError::BadCellSize(cells.len()));
+            }
+            for (m, cell) in mask.iter_mut().zip(cells.iter()) {
+                *m = cell;
+            }
+        }
+        for (cell, m) in key.iter_mut().zip(mask) {
+            *cell &= m;
+        }
+
+        let mut cells = Cells::new(map.raw_value()).iter().peekable();
+        let mut parent: Option<(Phandle, Node<'a>)> = None;
+
+        loop {
+            if cells.peek().is_none() {
+                return Err(FdtError::NotFound("interrupt-map entry"));
+            }
+            let child = take_cells(&mut cells, key_len)?;
+            let phandle = Phandle::from(
+                take_cells(&mut cells, 1)?
+                    .first()
+                    .copied()
+                    .unwrap_or_default(),
+            );
+            let node = match parent {
+                Some((p, ref node)) if p == phandle => node.clone(),
+                _ => self
+                    .fdt
+                    .get_node_by_phandle(phandle)
+                    .ok_or(FdtError::NotFound("interrupt-map parent"))?,
+            };
+            let parent_address =
+                take_cells(&mut cells, node.meta.address_cells.unwrap_or(0) as usize)?;
+            let parent_interrupt_cells = node
+                .meta
+                .interrupt_cells
+                .ok_or(FdtError::NotFound("#interrupt-cells"))?;
+            let parent_specifier = take_cells(&mut cells, parent_interrupt_cells as usize)?;
+
+            let matches = child
+                .iter()
+                .zip(mask)
+                .zip(key.iter())
+                .all(|((c, m), k)| c & m == *k);
+            if matches {
+                return Ok((node, parent_address, parent_specifier));
+            }
+            parent = Some((phandle, node));
+        }
+    }
+}
+
+/// One interrupt of a device, see [Node::interrupt_specs].
+pub struct InterruptSpec<'a> {
+    /// Interrupt parent the specifier is meant for, an interrupt controller
+    /// or a nexus, see [Node::map_interrupt].
+    pub parent: InterruptController<'a>,
+    /// Specifier in the format of the parent's `#interrupt-cells`.
+    pub specifier: Cells<'a>,
+    /// Matching entry of `interrupt-names`.
+    pub name: Option<&'a str>,
+}
+
+/// Iterator over [InterruptSpec], see [Node::interrupt_specs].
+pub struct InterruptSpecIter<'a> {
+    node: Node<'a>,
+    /// Value of `interrupts-extended`, or of `interrupts` when it is `false`.
+    extended: bool,
+    /// Interrupt parent of the node, for `interrupts`.
+    parent: Option<InterruptController<'a>>,
+    prop: Option<FdtReader<'a>>,
+    names: Option<StrList<'a>>,
+    index: usize,
+}
+
+impl<'a> InterruptSpecIter<'a> {
+    fn read(&mut self) -> Option<FdtResult<'a, InterruptSpec<'a>>> {
+        let p = self.prop.as_mut()?;
+        if p.is_empty() {
+            return None;
+        }
+        let node = &self.node;
+        let extended = self.extended;
+        let parent = &self.parent;
+
+        let mut read = move || {
+            let parent = if extended {
+                let phandle = Phandle::from(p.take_u32().ok_or(FdtError::Eof)?);
+                node.fdt
+                    .get_node_by_phandle(phandle)
+                    .map(|node| InterruptController { node })
+                    .ok_or(FdtError::NotFound("interrupt parent"))?
+            } else {
+                parent
+                    .clone()
+                    .ok_or(FdtError::NotFound("interrupt-parent"))?
+            };
+            let cells = parent.try_interrupt_cells()?;
+            if cells == 0 && !extended {
+                return Err(FdtError::BadCellSize(cells));
+            }
+            let len = cells.checked_mul(4).ok_or(FdtError::BadCellSize(cells))?;
+            let specifier = Cells::new(p.take(len).ok_or(FdtError::Eof)?);
+
+            Ok(InterruptSpec {
+                parent,
+                specifier,
+                name: None,
+            })
+        };
+        let spec = read();
+        if spec.is_err() {
+            self.prop = None;
+        }
+        Some(spec)
+    }
+}
+
+impl<'a> Iterator for InterruptSpecIter<'a> {
+    type Item = FdtResult<'a, InterruptSpec<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut spec = self.read()?;
+        if let Ok(spec) = &mut spec {
+            spec.name = self.names.and_then(|names| names.iter().nth(self.index));
+        }
+        self.index = self.index.saturating_add(1);
+        Some(spec)
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Interrupts of this device from `interrupts-extended`, or else from
+    /// `interrupts` and the interrupt parent. Empty if it has neither, ends
+    /// with an error on an entry that can't be read.
+    pub fn interrupt_specs(&self) -> InterruptSpecIter<'a> {
+        let (extended, prop) = match self.find_property("interrupts-extended") {
+            Some(prop) => (true, Some(prop)),
+            None => (false, self.find_property("interrupts")),
+        };
+
+        InterruptSpecIter {
+            node: self.clone(),
+            extended,
+            parent: if extended {
+                None
+            } else {
+                prop.as_ref().and_then(|_| self.interrupt_parent())
+            },
+            prop: prop.map(|p| p.data),
+            names: self
+                .find_property("interrupt-names")
+                .map(|p| StrList::new(p.raw_value())),
+            index: 0,
+        }
+    }
+
+    /// The interrupt listed as `name` in `interrupt-names`.
+    pub fn interrupt_by_name(&self, name: &str) -> FdtResult<'a, InterruptSpec<'a>> {
+        let names = self
+            .find_property("interrupt-names")
+            .ok_or(FdtError::NotFound("interrupt-names"))?;
+        let index = StrList::new(names.raw_value())
+            .iter()
+            .position(|n| n == name)
+            .ok_or(FdtError::NotFound("interrupt name"))?;
+
+        self.interrupt_specs()
+            .nth(index)
+            .ok_or(FdtError::NotFound("interrupt"))?
+    }
+}
+
+/// The next `n` cells of a property made of cells.
+fn take_cells<'a>(cells: &mut impl Iterator<Item = u32>, n: usize) -> FdtResult<'a, Specifier> {
+    let mut out = [0u32; Specifier::MAX_CELLS];
+    let row = out.get_mut(..n).ok_or(FdtError::BadCellSize(n))?;
+    for cell in row.iter_mut() {
+        *cell = cells.next().ok_or(FdtError::BadCell)?;
     }
+    Specifier::new(row).ok_or(FdtError::BadCellSize(n))
 }
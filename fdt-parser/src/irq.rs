@@ -0,0 +1,183 @@
+use crate::{
+    error::*,
+    interrupt::{InterruptController, InterruptControllerKind, InterruptSpec, ResolvedInterrupt},
+    Phandle, Specifier,
+};
+
+/// How a line signals an interrupt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Edge,
+    Level,
+}
+
+/// Active level of a level triggered line, or the edge of an edge triggered
+/// one, high meaning rising.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Polarity {
+    High,
+    Low,
+    /// Both edges.
+    Both,
+}
+
+/// Interrupt types of the ARM GIC bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GicInterrupt {
+    /// Shared peripheral interrupt.
+    Spi,
+    /// Private peripheral interrupt.
+    Ppi,
+    /// GICv3.1 extended SPI range.
+    ExtendedSpi,
+    /// GICv3.1 extended PPI range.
+    ExtendedPpi,
+    /// Message based interrupt of a GICv3 ITS, `GIC_IRQ_TYPE_LPI` in Linux.
+    Lpi,
+}
+
+/// Interrupt specifier decoded according to the binding of its controller,
+/// see [InterruptController::decode].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterruptSpecifier {
+    /// Interrupt number in the controller, such as the GIC INTID: SPIs start
+    /// at 32 and PPIs at 16.
+    pub hwirq: u32,
+    /// `None` if the specifier leaves it to the controller default.
+    pub trigger: Option<Trigger>,
+    /// `None` if the specifier leaves it to the controller default.
+    pub polarity: Option<Polarity>,
+    /// Type of a GIC interrupt, `None` for other controllers.
+    pub gic: Option<GicInterrupt>,
+    /// CPUs a GICv2 PPI is wired to, one bit per CPU interface.
+    pub ppi_cpu_mask: Option<u8>,
+    /// `ppi-partitions` child of a GICv3 the PPI is limited to.
+    pub ppi_partition: Option<Phandle>,
+}
+
+/// First cell of a GIC specifier for an LPI, from the Linux
+/// `arm-gic.h` binding header.
+const GIC_IRQ_TYPE_LPI: u32 = 0xa110c8ed;
+
+/// Layout of the specifiers of a controller.
+enum Format {
+    /// `<type number flags [partition]>`
+    Gic,
+    /// `<bank number>` of the BCM2835 ARM interrupt controller.
+    Bcm2835,
+    /// `<hwirq>`
+    OneCell,
+    /// `<hwirq flags>`
+    TwoCell,
+}
+
+fn format_of<'a>(controller: &InterruptController<'a>, cells: usize) -> FdtResult<'a, Format> {
+    match controller.kind() {
+        InterruptControllerKind::ArmGic | InterruptControllerKind::ArmGicV3 => Ok(Format::Gic),
+        InterruptControllerKind::Bcm2835ArmCtrl => Ok(Format::Bcm2835),
+        // bcm2836-l1-intc, PLICs, APLICs, GPIO controllers and most others
+        // follow one of the generic forms.
+        _ => match cells {
+            1 => Ok(Format::OneCell),
+            2 => Ok(Format::TwoCell),
+            _ => Err(FdtError::BadCellSize(cells)),
+        },
+    }
+}
+
+/// Decode `IRQ_TYPE_*` flags.
+fn sense<'a>(flags: u32) -> FdtResult<'a, (Option<Trigger>, Option<Polarity>)> {
+    Ok(match flags & 0xf {
+        0 => (None, None),
+        1 => (Some(Trigger::Edge), Some(Polarity::High)),
+        2 => (Some(Trigger::Edge), Some(Polarity::Low)),
+        3 => (Some(Trigger::Edge), Some(Polarity::Both)),
+        4 => (Some(Trigger::Level), Some(Polarity::High)),
+        8 => (Some(Trigger::Level), Some(Polarity::Low)),
+        _ => return Err(FdtError::BadCell),
+    })
+}
+
+impl<'a> InterruptController<'a> {
+    /// Decode `specifier` according to the binding of this controller,
+    /// chosen by its `compatible`: the ARM GIC, the BCM2835 ARM controller,
+    /// or the generic `<hwirq>` and `<hwirq flags>` forms used by the
+    /// BCM2836 local controller, RISC-V PLIC and APLIC and most others.
+    pub fn decode(&self, specifier: &[u32]) -> FdtResult<'a, InterruptSpecifier> {
+        let cell = |i: usize| {
+            specifier
+                .get(i)
+                .copied()
+                .ok_or(FdtError::BadCellSize(specifier.len()))
+        };
+        let mut out = InterruptSpecifier {
+            hwirq: cell(0)?,
+            trigger: None,
+            polarity: None,
+            gic: None,
+            ppi_cpu_mask: None,
+            ppi_partition: None,
+        };
+
+        match format_of(self, specifier.len())? {
+            Format::Gic => {
+                let (kind, base) = match cell(0)? {
+                    0 => (GicInterrupt::Spi, 32),
+                    1 => (GicInterrupt::Ppi, 16),
+                    2 => (GicInterrupt::ExtendedSpi, 4096),
+                    3 => (GicInterrupt::ExtendedPpi, 1056),
+                    GIC_IRQ_TYPE_LPI => (GicInterrupt::Lpi, 0),
+                    _ => return Err(FdtError::BadCell),
+                };
+                let flags = cell(2)?;
+                out.hwirq = cell(1)?.checked_add(base).ok_or(FdtError::BadCell)?;
+                (out.trigger, out.polarity) = sense(flags)?;
+                out.gic = Some(kind);
+                if kind == GicInterrupt::Ppi {
+                    out.ppi_cpu_mask = Some((flags >> 8 & 0xff) as u8).filter(|mask| *mask != 0);
+                    out.ppi_partition = specifier
+                        .get(3)
+                        .filter(|p| **p != 0)
+                        .map(|p| Phandle::from(*p));
+                }
+            }
+            Format::Bcm2835 => {
+                let bank = cell(0)?;
+                let number = cell(1)?;
+                if bank > 2 || number > 31 {
+                    return Err(FdtError::BadCell);
+                }
+                out.hwirq = bank << 5 | number;
+            }
+            Format::OneCell => {}
+            Format::TwoCell => (out.trigger, out.polarity) = sense(cell(1)?)?,
+        }
+
+        Ok(out)
+    }
+}
+
+impl<'a> InterruptSpec<'a> {
+    /// Decode the specifier for the interrupt parent, see
+    /// [InterruptController::decode]. Resolve it first if the parent is a
+    /// nexus.
+    pub fn decode(&self) -> FdtResult<'a, InterruptSpecifier> {
+        if self.specifier.len() > Specifier::MAX_CELLS {
+            return Err(FdtError::BadCellSize(self.specifier.len()));
+        }
+        let mut cells = [0u32; Specifier::MAX_CELLS];
+        for (out, cell) in cells.iter_mut().zip(self.specifier.iter()) {
+            *out = cell;
+        }
+        let cells = cells.get(..self.specifier.len()).unwrap_or_default();
+        self.parent.decode(cells)
+    }
+}
+
+impl<'a> ResolvedInterrupt<'a> {
+    /// Decode the specifier for the controller, see
+    /// [InterruptController::decode].
+    pub fn decode(&self) -> FdtResult<'a, InterruptSpecifier> {
+        self.controller.decode(&self.specifier)
+    }
+}
@@ -1,30 +1,66 @@
 #![cfg_attr(not(test), no_std)]
 #![doc = include_str!("../README.md")]
+// The library must not panic on any input bytes, nor overflow on them. The
+// few legacy accessors that do are marked `#[allow]` and have a fallible
+// `try_` form.
+#![deny(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::unreachable,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::arithmetic_side_effects
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 mod chosen;
 mod clocks;
 mod define;
+mod dma;
 pub mod error;
 mod fdt;
+mod gpio;
+mod index;
 mod interrupt;
+mod irq;
 mod memory;
 mod meta;
 mod node;
 mod pci;
+mod phandle;
 mod property;
 mod read;
+mod reset;
+mod thermal;
+mod validate;
+mod value;
 
 use define::*;
 
 pub use chosen::Chosen;
-pub use clocks::ClockRef;
-pub use define::{FdtHeader, MemoryRegion};
+pub use clocks::{AssignedClock, ClockRef, ClockTree};
+pub use define::{CellAddress, FdtHeader, MemoryRegion, NodeOffset, Phandle, Specifier};
+pub use dma::{DmaChannel, DmaLimits, DmaWindow};
 pub use error::FdtError;
-pub use fdt::Fdt;
-pub use interrupt::InterruptController;
+pub use fdt::{Fdt, NodePath, DEFAULT_DEPTH};
+pub use gpio::{GpioFlags, GpioHog, GpioRange, GpioSpec, HogState};
+pub use index::{FdtIndex, IndexEntry};
+pub use interrupt::{
+    CascadeIter, InterruptController, InterruptControllerKind, InterruptSpec, InterruptSpecIter,
+    ResolvedInterrupt,
+};
+pub use irq::{GicInterrupt, InterruptSpecifier, Polarity, Trigger};
 pub use node::Node;
-pub use pci::{Pci, PciRange, PciSpace};
-pub use property::Property;
+pub use pci::{Pci, PciIntPin, PciRange, PciSpace};
+pub use phandle::{PhandleArgs, PhandleArgsIter};
+pub use property::{Array, BigEndian, FromProperty, Property};
+pub use reset::{ResetController, ResetSpec};
+pub use thermal::{CoolingDevice, CoolingMap, ThermalZone, ThermalZones, TripPoint, TripType};
+pub use value::{Cells, PropertyValue, StrList};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Status {
@@ -20,7 +20,7 @@ impl<'a> Memory<'a> {
             Some(r) => {
                 let reg = r.next()?;
                 Some(MemoryRegion {
-                    address: reg.address as usize as _,
+                    address: usize::try_from(reg.address).ok()? as _,
                     size: reg.size.unwrap_or_default(),
                 })
             }
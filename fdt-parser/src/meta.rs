@@ -1,7 +1,7 @@
-use crate::{FdtRangeSilce, Phandle};
+use crate::Phandle;
 
 #[derive(Clone, Default)]
-pub(crate) struct MetaData<'a> {
+pub(crate) struct MetaData {
     pub address_cells: Option<u8>,
     pub size_cells: Option<u8>,
     pub clock_cells: Option<u8>,
@@ -9,6 +9,28 @@ pub(crate) struct MetaData<'a> {
     pub gpio_cells: Option<u8>,
     pub dma_cells: Option<u8>,
     pub cooling_cells: Option<u8>,
-    pub range: Option<FdtRangeSilce<'a>>,
     pub interrupt_parent: Option<Phandle>,
 }
+
+impl MetaData {
+    /// Metadata seen by the children of a node: its own values take
+    /// precedence over the ones it inherited from `parents`.
+    pub fn merge(&self, parents: &MetaData) -> MetaData {
+        macro_rules! pick {
+            ($field:ident) => {
+                self.$field.clone().or_else(|| parents.$field.clone())
+            };
+        }
+
+        MetaData {
+            address_cells: pick!(address_cells),
+            size_cells: pick!(size_cells),
+            clock_cells: pick!(clock_cells),
+            interrupt_cells: pick!(interrupt_cells),
+            gpio_cells: pick!(gpio_cells),
+            dma_cells: pick!(dma_cells),
+            cooling_cells: pick!(cooling_cells),
+            interrupt_parent: pick!(interrupt_parent),
+        }
+    }
+}
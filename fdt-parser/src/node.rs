@@ -1,14 +1,20 @@
-use core::iter;
+use core::{
+    fmt::Write,
+    hash::{Hash, Hasher},
+    iter,
+    ops::Range,
+};
 
 use crate::{
     clocks::{ClockRef, ClocksIter},
     error::{FdtError, FdtResult},
+    fdt::{FdtIter, DEFAULT_DEPTH},
     interrupt::InterruptController,
     meta::MetaData,
     pci::Pci,
-    property::Property,
+    property::{FromProperty, Property},
     read::{FdtReader, U32Array2D},
-    Fdt, FdtRangeSilce, FdtReg, Phandle, Status, Token,
+    CellAddress, Fdt, FdtRange, FdtRangeSilce, FdtReg, NodeOffset, Phandle, Status, Token,
 };
 
 #[derive(Clone)]
@@ -16,10 +22,12 @@ pub struct Node<'a> {
     pub level: usize,
     pub name: &'a str,
     pub(crate) fdt: &'a Fdt<'a>,
+    /// struct block 中 `BEGIN_NODE` 的偏移
+    pub(crate) offset: usize,
     /// 父节点的元数据
-    pub(crate) meta_parents: MetaData<'a>,
+    pub(crate) meta_parents: MetaData,
     /// 当前节点的元数据
-    pub(crate) meta: MetaData<'a>,
+    pub(crate) meta: MetaData,
     body: FdtReader<'a>,
 }
 
@@ -28,13 +36,15 @@ impl<'a> Node<'a> {
         fdt: &'a Fdt<'a>,
         level: usize,
         name: &'a str,
+        offset: usize,
         reader: FdtReader<'a>,
-        meta_parents: MetaData<'a>,
-        meta: MetaData<'a>,
+        meta_parents: MetaData,
+        meta: MetaData,
     ) -> Self {
         Self {
             fdt,
             level,
+            offset,
             body: reader,
             name,
             meta,
@@ -46,6 +56,130 @@ impl<'a> Node<'a> {
         self.name
     }
 
+    /// Handle to this node, see [Fdt::node_at].
+    pub fn offset(&self) -> NodeOffset {
+        NodeOffset(self.offset)
+    }
+
+    /// Write the full path of this node, such as `/soc/serial@7e215040`.
+    /// Fails without a complete path if the walk from the root doesn't reach
+    /// this node.
+    pub fn path<W: Write>(&self, w: &mut W) -> core::fmt::Result {
+        if self.level < 2 {
+            return w.write_char('/');
+        }
+        let node = self.fdt.descend(self.offset, self.level, |node| {
+            if node.level > 1 {
+                w.write_char('/')?;
+                w.write_str(node.name)?;
+            }
+            Ok(())
+        })?;
+        match node {
+            Some(node) if node.offset == self.offset => Ok(()),
+            _ => Err(core::fmt::Error),
+        }
+    }
+
+    /// Owned version of [Node::path], `None` if the path can't be built.
+    #[cfg(feature = "alloc")]
+    pub fn path_string(&self) -> Option<alloc::string::String> {
+        let mut path = alloc::string::String::new();
+        self.path(&mut path).ok()?;
+        Some(path)
+    }
+
+    /// The node containing this one, `None` for the root node.
+    pub fn parent(&self) -> Option<Node<'a>> {
+        if self.level < 2 {
+            return None;
+        }
+        self.fdt
+            .ancestor_at(self.offset, self.level.saturating_sub(1))
+    }
+
+    /// The child of this node identified by `offset`, rebuilt from its own
+    /// token and the metadata of this node without walking the tree.
+    /// Returns `None` if `offset` is not the start of a node after this one;
+    /// that it is a direct child is not checked.
+    pub fn child_at(&self, offset: NodeOffset) -> Option<Node<'a>> {
+        let mut reader = self.fdt.struct_reader(offset.0);
+        if offset.0 <= self.offset
+            || !offset.0.is_multiple_of(size_of::<u32>())
+            || reader.take_token()? != Token::BeginNode
+        {
+            return None;
+        }
+        let meta = self.meta.merge(&self.meta_parents);
+        let level = self.level.checked_add(1)?;
+        FdtIter::<1>::subtree(self.fdt, offset.0, level, meta)
+            .next()?
+            .ok()
+    }
+
+    /// All nodes containing this one, from the parent up to the root node.
+    pub fn ancestors(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        iter::successors(self.parent(), |node| node.parent())
+    }
+
+    /// Direct children of this node. The subtree of each child is skipped
+    /// over, not decoded.
+    pub fn children(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        let node = self.clone();
+        self.child_ranges()
+            .filter_map(move |range| node.child_at(NodeOffset(range.start)))
+    }
+
+    /// All nodes below this one, depth-first, down to [DEFAULT_DEPTH] levels
+    /// below it. Yields the error that ends the walk, like [Fdt::all_nodes].
+    pub fn descendants(&self) -> impl Iterator<Item = FdtResult<'a, Node<'a>>> + 'a {
+        self.subtree().skip(1)
+    }
+
+    /// The next node sharing the same parent.
+    pub fn next_sibling(&self) -> Option<Node<'a>> {
+        let mut reader = self.body.clone();
+        reader.skip_node_body()?;
+        loop {
+            match reader.take_token()? {
+                Token::BeginNode => break,
+                Token::Nop => {}
+                _ => return None,
+            }
+        }
+        let offset = self
+            .fdt
+            .struct_offset(&reader)
+            .saturating_sub(size_of::<u32>());
+
+        FdtIter::<1>::subtree(self.fdt, offset, self.level, self.meta_parents.clone())
+            .next()?
+            .ok()
+    }
+
+    fn subtree(&self) -> FdtIter<'a> {
+        FdtIter::subtree(self.fdt, self.offset, self.level, self.meta_parents.clone())
+    }
+
+    /// Struct block ranges covered by each direct child, from its
+    /// `BEGIN_NODE` to past its `END_NODE`.
+    pub(crate) fn child_ranges(&self) -> impl Iterator<Item = Range<usize>> + 'a {
+        let fdt = self.fdt;
+        let mut reader = self.body.clone();
+        iter::from_fn(move || loop {
+            match reader.take_token()? {
+                Token::BeginNode => {
+                    let start = fdt.struct_offset(&reader).saturating_sub(size_of::<u32>());
+                    reader.skip_node()?;
+                    return Some(start..fdt.struct_offset(&reader));
+                }
+                Token::Prop => reader.skip_prop()?,
+                Token::Nop => {}
+                _ => return None,
+            }
+        })
+    }
+
     pub fn propertys(&self) -> impl Iterator<Item = Property<'a>> + '_ {
         let reader = self.body.clone();
         PropIter {
@@ -58,32 +192,113 @@ impl<'a> Node<'a> {
         self.propertys().find(|x| x.name.eq(name))
     }
 
+    /// Value of the property `name` as a `T`, see [FromProperty].
+    ///
+    /// ```ignore
+    /// let freq: u32 = node.get("clock-frequency")?;
+    /// let dma_coherent: bool = node.get("dma-coherent")?;
+    /// let names: Option<StrList> = node.get("clock-names")?;
+    /// ```
+    pub fn get<T: FromProperty<'a>>(&self, name: &str) -> FdtResult<'a, T> {
+        match self.find_property(name) {
+            Some(prop) => T::from_property(&prop),
+            None => T::missing(),
+        }
+    }
+
+    /// `None` if there is no `reg` or it is malformed, see [Node::try_reg].
     pub fn reg(&self) -> Option<impl Iterator<Item = FdtReg> + 'a> {
-        let mut iter = self.propertys();
-        let reg = iter.find(|x| x.name.eq("reg"))?;
+        self.try_reg().ok()
+    }
+
+    /// Like [Node::reg], but tells why the `reg` property can't be read.
+    /// Missing `#address-cells`/`#size-cells` fall back to the
+    /// specification defaults of 2 and 1.
+    pub fn try_reg(&self) -> FdtResult<'a, impl Iterator<Item = FdtReg> + 'a> {
+        let reg = self.find_property("reg").ok_or(FdtError::NotFound("reg"))?;
+        let address_cell = self.meta_parents.address_cells.unwrap_or(2);
+        let size_cell = self.meta_parents.size_cells.unwrap_or(1);
 
-        Some(RegIter {
-            size_cell: self.meta_parents.size_cells.unwrap(),
-            address_cell: self.meta_parents.address_cells.unwrap(),
+        if !(1..=CellAddress::MAX_CELLS as u8).contains(&address_cell) {
+            return Err(FdtError::BadCellSize(address_cell as _));
+        }
+        if size_cell > CellAddress::MAX_CELLS as u8 {
+            return Err(FdtError::BadCellSize(size_cell as _));
+        }
+        let entry_len =
+            usize::from(address_cell.saturating_add(size_cell)).saturating_mul(size_of::<u32>());
+        if !reg.raw_value().len().is_multiple_of(entry_len) {
+            return Err(FdtError::BadCell);
+        }
+
+        Ok(RegIter {
+            size_cell,
+            address_cell,
             prop: reg,
-            ranges: self.meta_parents.range.clone(),
+            buses: self.bus_ranges("ranges").rev(),
+        })
+    }
+
+    /// Translate `address`, an address on the bus this node sits on such as
+    /// one of its `reg` entries, to a CPU physical address by applying the
+    /// `ranges` of every ancestor up to the root.
+    ///
+    /// An empty `ranges` maps addresses unchanged, a missing one means the
+    /// bus is not memory-mapped and fails with [FdtError::NotMemoryMapped].
+    pub fn translate_address(&self, address: CellAddress) -> FdtResult<'a, CellAddress> {
+        let buses = self.bus_ranges("ranges").rev();
+        translate(address, buses, false, FdtRange::translate)
+    }
+
+    /// The `prop` ranges (`ranges` or `dma-ranges`) of each bus between the
+    /// root and this node, outermost first, `None` for a bus without it.
+    pub(crate) fn bus_ranges(
+        &self,
+        prop: &'static str,
+    ) -> impl DoubleEndedIterator<Item = Option<FdtRangeSilce<'a>>> + ExactSizeIterator + Clone + 'a
+    {
+        // The buses of the first levels are collected in a single walk from
+        // the root, deeper ones are looked up one by one.
+        let mut cached: [Option<FdtRangeSilce<'a>>; DEFAULT_DEPTH] = core::array::from_fn(|_| None);
+        self.fdt
+            .descend(self.offset, self.level.saturating_sub(1), |bus| {
+                if let Some(slot) = bus.level.checked_sub(2).and_then(|i| cached.get_mut(i)) {
+                    *slot = bus.ranges_of(prop);
+                }
+                Ok::<_, ()>(())
+            })
+            .ok();
+
+        let fdt = self.fdt;
+        let offset = self.offset;
+        (2..self.level).map(move |level| match cached.get(level.saturating_sub(2)) {
+            Some(ranges) => ranges.clone(),
+            None => fdt
+                .ancestor_at(offset, level)
+                .and_then(|bus| bus.ranges_of(prop)),
         })
     }
 
     pub(crate) fn node_ranges(&self) -> Option<FdtRangeSilce<'a>> {
-        let prop = self.find_property("ranges")?;
+        self.ranges_of("ranges")
+    }
+
+    /// A `ranges`-like property, mapping addresses of the children of this
+    /// node to addresses of its parent.
+    pub(crate) fn ranges_of(&self, prop: &str) -> Option<FdtRangeSilce<'a>> {
+        let prop = self.find_property(prop)?;
 
         Some(FdtRangeSilce::new(
-            self.meta.address_cells.unwrap(),
-            self.meta_parents.address_cells.unwrap(),
-            self.meta.size_cells.unwrap(),
+            self.meta.address_cells.unwrap_or(2),
+            self.meta_parents.address_cells.unwrap_or(2),
+            self.meta.size_cells.unwrap_or(1),
             prop.data.clone(),
         ))
     }
 
     pub(crate) fn node_interrupt_parent(&self) -> Option<Phandle> {
         let prop = self.find_property("interrupt-parent")?;
-        Some(prop.u32().into())
+        prop.try_u32().ok().map(Phandle::from)
     }
 
     /// Find [InterruptController] from current node or its parent
@@ -135,25 +350,38 @@ impl<'a> Node<'a> {
         })
     }
 
+    /// `phandle`, or the legacy `linux,phandle` of older blobs.
     pub fn phandle(&self) -> Option<Phandle> {
-        let prop = self.find_property("phandle")?;
-        Some(prop.u32().into())
+        let prop = self
+            .find_property("phandle")
+            .or_else(|| self.find_property("linux,phandle"))?;
+        prop.try_u32().ok().map(Phandle::from)
     }
 
     pub fn interrupts(&self) -> Option<impl Iterator<Item = impl Iterator<Item = u32> + 'a> + 'a> {
         let prop = self.find_property("interrupts")?;
-        let cell_size = self.interrupt_parent()?.interrupt_cells();
+        let cell_size = self.interrupt_parent()?.try_interrupt_cells().ok()?;
 
         Some(U32Array2D::new(prop.raw_value(), cell_size))
     }
 
-    pub fn clocks(&'a self) -> impl Iterator<Item = ClockRef<'a>> + 'a {
+    /// Clocks of this consumer, from `clocks` and `clock-names`. Ends early,
+    /// without telling, at an entry that can't be read, such as one whose
+    /// provider has no `#clock-cells`; [Node::try_clocks] reports it.
+    pub fn clocks(&self) -> impl Iterator<Item = ClockRef<'a>> + 'a {
         ClocksIter::new(self)
     }
 
+    /// Like [Node::clocks], but yields the error that ends the list early,
+    /// e.g. a provider without `#clock-cells`.
+    pub fn try_clocks(&self) -> impl Iterator<Item = FdtResult<'a, ClockRef<'a>>> + 'a {
+        let mut iter = ClocksIter::new(self);
+        iter::from_fn(move || iter.next_clock())
+    }
+
     pub fn clock_frequency(&self) -> Option<u32> {
         let prop = self.find_property("clock-frequency")?;
-        Some(prop.u32())
+        prop.try_u32().ok()
     }
 
     pub fn into_pci(self) -> Option<Pci<'a>> {
@@ -166,7 +394,7 @@ impl<'a> Node<'a> {
 
     pub fn status(&self) -> Option<Status> {
         let prop = self.find_property("status")?;
-        let s = prop.str();
+        let s = prop.try_str().ok()?;
 
         if s.contains("disabled") {
             return Some(Status::Disabled);
@@ -180,41 +408,82 @@ impl<'a> Node<'a> {
     }
 }
 
-struct RegIter<'a> {
+/// Nodes are equal when they are the same node of the same blob.
+impl PartialEq for Node<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset && core::ptr::eq(self.fdt.data, other.fdt.data)
+    }
+}
+
+impl Eq for Node<'_> {}
+
+impl Hash for Node<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset.hash(state);
+    }
+}
+
+/// Map `address` through the ranges of each bus in turn with `step`. Empty
+/// ranges map addresses unchanged, so do missing ones if
+/// `missing_is_identity`, otherwise they fail with
+/// [FdtError::NotMemoryMapped].
+pub(crate) fn translate<'a>(
+    address: CellAddress,
+    buses: impl Iterator<Item = Option<FdtRangeSilce<'a>>>,
+    missing_is_identity: bool,
+    step: impl Fn(&FdtRange, CellAddress) -> Option<CellAddress>,
+) -> FdtResult<'a, CellAddress> {
+    let mut address = address;
+    for ranges in buses {
+        let ranges = match ranges {
+            Some(ranges) => ranges,
+            None if missing_is_identity => continue,
+            None => return Err(FdtError::NotMemoryMapped),
+        };
+        if ranges.is_empty() {
+            continue;
+        }
+        address = ranges
+            .iter()
+            .find_map(|range| step(&range, address))
+            .ok_or(FdtError::NoMatchingRange)?;
+    }
+    Ok(address)
+}
+
+struct RegIter<'a, B> {
     size_cell: u8,
     address_cell: u8,
     prop: Property<'a>,
-    ranges: Option<FdtRangeSilce<'a>>,
+    /// `ranges` of the ancestors, innermost first, read once for all
+    /// entries.
+    buses: B,
 }
-impl Iterator for RegIter<'_> {
+impl<'a, B> Iterator for RegIter<'a, B>
+where
+    B: Iterator<Item = Option<FdtRangeSilce<'a>>> + Clone,
+{
     type Item = FdtReg;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let child_bus_address = self.prop.data.take_by_cell_size(self.address_cell)?;
-
-        let mut address = child_bus_address;
-
-        if let Some(ranges) = &self.ranges {
-            for one in ranges.iter() {
-                let range_child_bus_address = one.child_bus_address().as_u64();
-                let range_parent_bus_address = one.parent_bus_address().as_u64();
+        let child_bus_address = self.prop.data.take_address(self.address_cell)?;
 
-                if child_bus_address >= range_child_bus_address
-                    && child_bus_address < range_child_bus_address + one.size
-                {
-                    address =
-                        child_bus_address - range_child_bus_address + range_parent_bus_address;
-                }
-            }
-        }
+        let cpu_address = translate(
+            child_bus_address,
+            self.buses.clone(),
+            false,
+            FdtRange::translate,
+        )
+        .ok();
 
         let size = if self.size_cell > 0 {
-            Some(self.prop.data.take_by_cell_size(self.size_cell)? as usize)
+            usize::try_from(self.prop.data.take_address(self.size_cell)?).ok()
         } else {
             None
         };
         Some(FdtReg {
-            address,
+            address: cpu_address.unwrap_or(child_bus_address),
+            cpu_address,
             child_bus_address,
             size,
         })
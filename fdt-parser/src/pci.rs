@@ -1,6 +1,9 @@
 use core::{fmt::Debug, ops::Range};
 
-use crate::{error::FdtResult, node::Node, read::FdtReader, FdtError, FdtRangeIter};
+use crate::{
+    error::FdtResult, interrupt::ResolvedInterrupt, node::Node, read::FdtReader, FdtError,
+    FdtRangeIter,
+};
 
 pub struct Pci<'a> {
     pub node: Node<'a>,
@@ -16,7 +19,7 @@ impl<'a> Pci<'a> {
         Some(start as usize..end as usize)
     }
 
-    pub fn ranges(&self) -> FdtResult<impl Iterator<Item = PciRange> + 'a> {
+    pub fn ranges(&self) -> FdtResult<'_, impl Iterator<Item = PciRange> + 'a> {
         let ranges = self
             .node
             .node_ranges()
@@ -26,6 +29,34 @@ impl<'a> Pci<'a> {
 
         Ok(PciRangeIter { iter })
     }
+
+    /// Controller and specifier of the legacy INTx interrupt `pin` of a
+    /// device function below this host bridge, routed by its
+    /// `interrupt-map`.
+    ///
+    /// This is the routing of the host bridge itself: a device behind a
+    /// PCI-PCI bridge without a node of its own has its pin swizzled first,
+    /// as the PCI specification requires.
+    pub fn map_intx(
+        &self,
+        bus: u8,
+        device: u8,
+        function: u8,
+        pin: PciIntPin,
+    ) -> FdtResult<'a, ResolvedInterrupt<'a>> {
+        let phys_hi =
+            (bus as u32) << 16 | ((device & 0x1f) as u32) << 11 | ((function & 0x7) as u32) << 8;
+        self.node.map_interrupt(&[phys_hi, 0, 0], &[pin as u32])
+    }
+}
+
+/// Legacy PCI interrupt pin, numbered like the `Interrupt Pin` register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PciIntPin {
+    A = 1,
+    B,
+    C,
+    D,
 }
 
 pub struct PciRangeIter<'a> {
@@ -37,13 +68,13 @@ impl Iterator for PciRangeIter<'_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let one = self.iter.next()?;
-        let mut child = one.child_bus_address();
-        let cpu_address = one.parent_bus_address().as_u64();
-        let size = one.size;
+        let child = one.child_bus_address();
+        let cpu_address = u64::try_from(one.parent_bus_address()).ok()?;
+        let size = u64::try_from(one.size).ok()?;
 
-        let hi = child.next().unwrap();
-        let mid = child.next().unwrap();
-        let low = child.next().unwrap();
+        let hi = child.cell(0)?;
+        let mid = child.cell(1)?;
+        let low = child.cell(2)?;
 
         let ss = (hi >> 24) & 0b11;
         let prefetchable = (hi & 1 << 30) > 0;
@@ -52,11 +83,10 @@ impl Iterator for PciRangeIter<'_> {
             0b00 => PciSpace::Configuration,
             0b01 => PciSpace::IO,
             0b10 => PciSpace::Memory32,
-            0b11 => PciSpace::Memory64,
-            _ => panic!(),
+            _ => PciSpace::Memory64,
         };
 
-        let child_bus_address = (mid as u64) << 32 | low as u64;
+        let child_bus_address = ((mid as u64) << 32) | low as u64;
 
         Some(PciRange {
             space,
@@ -0,0 +1,257 @@
+use crate::{
+    error::*,
+    node::Node,
+    property::Property,
+    read::FdtReader,
+    value::{Cells, StrList},
+    Phandle, Specifier,
+};
+
+/// Nexus nodes [Node::map_specifier] crosses before failing with TooDeep.
+const MAX_NEXUS_HOPS: usize = 16;
+
+/// A provider node and the arguments a consumer gives it, one entry of a
+/// property such as `resets = <&rst 3>`, see [Node::parse_phandle_with_args].
+#[derive(Clone)]
+pub struct PhandleArgs<'a> {
+    pub provider: Node<'a>,
+    pub args: Specifier,
+}
+
+/// How many argument cells follow each phandle.
+#[derive(Clone, Copy)]
+enum ArgCount<'a> {
+    /// Read from this `#<x>-cells` property of the provider.
+    Property(&'a str),
+    /// Read from the `#<stem>-cells` property of the provider.
+    Stem(&'a str),
+    Fixed(usize),
+}
+
+/// Iterator over the entries of a phandle list, see
+/// [Node::parse_phandle_with_args].
+pub struct PhandleArgsIter<'a> {
+    node: Node<'a>,
+    prop: Option<FdtReader<'a>>,
+    count: ArgCount<'a>,
+}
+
+impl<'a> PhandleArgsIter<'a> {
+    fn read(
+        p: &mut FdtReader<'a>,
+        node: &Node<'a>,
+        count: ArgCount<'a>,
+    ) -> FdtResult<'a, PhandleArgs<'a>> {
+        let phandle = Phandle::from(p.take_u32().ok_or(FdtError::Eof)?);
+        let provider = node
+            .fdt
+            .get_node_by_phandle(phandle)
+            .ok_or(FdtError::DanglingPhandle(phandle))?;
+        let count = match count {
+            ArgCount::Property(name) => provider
+                .find_property(name)
+                .ok_or(FdtError::MissingProperty)?
+                .try_u32()? as usize,
+            ArgCount::Stem(stem) => provider.specifier_cells(stem)?,
+            ArgCount::Fixed(count) => count,
+        };
+
+        let mut args = [0u32; Specifier::MAX_CELLS];
+        let row = args.get_mut(..count).ok_or(FdtError::BadCellSize(count))?;
+        for cell in row.iter_mut() {
+            *cell = p.take_u32().ok_or(FdtError::Eof)?;
+        }
+
+        Ok(PhandleArgs {
+            provider,
+            args: Specifier::new(row).ok_or(FdtError::BadCellSize(count))?,
+        })
+    }
+}
+
+impl<'a> Iterator for PhandleArgsIter<'a> {
+    type Item = FdtResult<'a, PhandleArgs<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.prop.as_mut()?;
+        if p.is_empty() {
+            return None;
+        }
+        // A null phandle is an empty entry without arguments, used to skip
+        // an index.
+        if p.remaining().get(..4) == Some(&[0; 4]) {
+            p.take_u32();
+            return Some(Err(FdtError::NullPhandle));
+        }
+        let args = Self::read(p, &self.node, self.count);
+        if args.is_err() {
+            self.prop = None;
+        }
+        Some(args)
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Entries of a phandle list such as `resets` or `power-domains`, each a
+    /// phandle followed by as many argument cells as the `cells_name`
+    /// property of the provider says, like Linux
+    /// `of_parse_phandle_with_args`.
+    ///
+    /// A null phandle yields [FdtError::NullPhandle] for its entry. A dangling
+    /// phandle, a provider without `cells_name` or truncated arguments yield
+    /// an error that ends the list.
+    pub fn parse_phandle_with_args(
+        &self,
+        list_prop: &str,
+        cells_name: &'a str,
+    ) -> PhandleArgsIter<'a> {
+        self.phandle_args(list_prop, ArgCount::Property(cells_name))
+    }
+
+    /// Like [Node::parse_phandle_with_args], for bindings where every
+    /// phandle is followed by `count` cells.
+    pub fn parse_phandle_with_fixed_args(
+        &self,
+        list_prop: &str,
+        count: usize,
+    ) -> PhandleArgsIter<'a> {
+        self.phandle_args(list_prop, ArgCount::Fixed(count))
+    }
+
+    fn phandle_args(&self, list_prop: &str, count: ArgCount<'a>) -> PhandleArgsIter<'a> {
+        PhandleArgsIter {
+            node: self.clone(),
+            prop: self.find_property(list_prop).map(|p| p.data),
+            count,
+        }
+    }
+
+    /// The entry of `list_prop` named `name` in the matching `*-names`
+    /// property, such as `reset-names` for `resets` or `mbox-names` for
+    /// `mboxes`.
+    pub fn parse_phandle_with_args_by_name(
+        &self,
+        list_prop: &str,
+        cells_name: &'a str,
+        name: &str,
+    ) -> FdtResult<'a, PhandleArgs<'a>> {
+        let index = self
+            .entry_names(list_prop)
+            .ok_or(FdtError::NotFound("*-names"))?
+            .iter()
+            .position(|n| n == name)
+            .ok_or(FdtError::NotFound("name"))?;
+
+        self.parse_phandle_with_args(list_prop, cells_name)
+            .nth(index)
+            .ok_or(FdtError::NotFound("phandle"))?
+    }
+
+    /// Entries of a phandle list such as `gpios` or `pwms` whose providers
+    /// may be nexus nodes, resolved through their `<stem>-map` to the final
+    /// provider, like Linux `of_parse_phandle_with_args_map`. Argument cells
+    /// follow the `#<stem>-cells` of each provider.
+    pub fn parse_phandle_with_args_map(
+        &self,
+        list_prop: &str,
+        stem: &'a str,
+    ) -> impl Iterator<Item = FdtResult<'a, PhandleArgs<'a>>> + 'a {
+        self.phandle_args(list_prop, ArgCount::Stem(stem))
+            .map(move |args| args.and_then(|args| args.provider.map_specifier(stem, &args.args)))
+    }
+
+    /// Follow the specifier `args` of this provider through `<stem>-map`
+    /// nexus nodes, such as `gpio-map` for the `gpio` stem, until a provider
+    /// without a map. Itself with `args` if this node is no nexus.
+    ///
+    /// Each hop masks `args` with `<stem>-map-mask` to find the matching
+    /// row, then copies the bits selected by `<stem>-map-pass-thru` from
+    /// `args` into the specifier of the row's parent, as devicetree
+    /// specification §2.5 describes.
+    pub fn map_specifier(&self, stem: &str, args: &[u32]) -> FdtResult<'a, PhandleArgs<'a>> {
+        let mut provider = self.clone();
+        let mut args = Specifier::new(args).ok_or(FdtError::BadCellSize(args.len()))?;
+
+        for _ in 0..MAX_NEXUS_HOPS {
+            let Some(map) = provider.stem_property(stem, "-map") else {
+                return Ok(PhandleArgs { provider, args });
+            };
+            let cells = provider.specifier_cells(stem)?;
+            if args.len() != cells {
+                return Err(FdtError::BadCellSize(args.len()));
+            }
+            let cell = |prop: Option<Property<'a>>, i: usize, default: u32| {
+                prop.and_then(|p| Cells::new(p.raw_value()).iter().nth(i))
+                    .unwrap_or(default)
+            };
+            let mask = provider.stem_property(stem, "-map-mask");
+            let pass_thru = provider.stem_property(stem, "-map-pass-thru");
+
+            let mut row = FdtReader::new(map.raw_value());
+            (provider, args) = loop {
+                if row.is_empty() {
+                    return Err(FdtError::NotFound("<specifier>-map entry"));
+                }
+                let mut matches = true;
+                for (i, arg) in args.iter().enumerate() {
+                    let child = row.take_u32().ok_or(FdtError::Eof)?;
+                    matches &= (child ^ arg) & cell(mask.clone(), i, u32::MAX) == 0;
+                }
+                let phandle = Phandle::from(row.take_u32().ok_or(FdtError::Eof)?);
+                let parent = self
+                    .fdt
+                    .get_node_by_phandle(phandle)
+                    .ok_or(FdtError::DanglingPhandle(phandle))?;
+                let parent_cells = parent.specifier_cells(stem)?;
+                let mut parent_args = [0u32; Specifier::MAX_CELLS];
+                let parent_args = parent_args
+                    .get_mut(..parent_cells)
+                    .ok_or(FdtError::BadCellSize(parent_cells))?;
+                for (i, out) in parent_args.iter_mut().enumerate() {
+                    let pass = cell(pass_thru.clone(), i, 0);
+                    let arg = args.get(i).copied().unwrap_or(0);
+                    *out = row.take_u32().ok_or(FdtError::Eof)? & !pass | arg & pass;
+                }
+                if matches {
+                    let parent_args =
+                        Specifier::new(parent_args).ok_or(FdtError::BadCellSize(parent_cells))?;
+                    break (parent, parent_args);
+                }
+            };
+        }
+
+        Err(FdtError::TooDeep)
+    }
+
+    /// `#<stem>-cells` of this provider.
+    fn specifier_cells(&self, stem: &str) -> FdtResult<'a, usize> {
+        let prop = self
+            .propertys()
+            .find(|p| {
+                p.name
+                    .strip_prefix('#')
+                    .and_then(|name| name.strip_suffix("-cells"))
+                    == Some(stem)
+            })
+            .ok_or(FdtError::MissingProperty)?;
+        Ok(prop.try_u32()? as usize)
+    }
+
+    /// The `<stem><suffix>` property, such as `gpio-map-mask`.
+    fn stem_property(&self, stem: &str, suffix: &str) -> Option<Property<'a>> {
+        self.propertys()
+            .find(|p| p.name.strip_prefix(stem) == Some(suffix))
+    }
+
+    /// The `*-names` property naming the entries of `list_prop`.
+    pub(crate) fn entry_names(&self, list_prop: &str) -> Option<StrList<'a>> {
+        let stem = match list_prop {
+            "mboxes" => "mbox",
+            _ => list_prop.strip_suffix('s').unwrap_or(list_prop),
+        };
+        let prop = self
+            .propertys()
+            .find(|p| p.name.strip_suffix("-names") == Some(stem))?;
+        Some(StrList::new(prop.raw_value()))
+    }
+}
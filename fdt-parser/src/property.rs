@@ -1,6 +1,10 @@
-use core::ffi::CStr;
+use core::{ffi::CStr, marker::PhantomData};
 
-use crate::read::FdtReader;
+use crate::{
+    error::{FdtError, FdtResult},
+    read::FdtReader,
+    Cells, Phandle, StrList,
+};
 
 #[derive(Clone)]
 pub struct Property<'a> {
@@ -13,18 +17,218 @@ impl<'a> Property<'a> {
         self.data.remaining()
     }
 
+    /// First cell of the value.
+    pub fn try_u32(&self) -> FdtResult<'a, u32> {
+        self.data.clone().take_u32().ok_or(FdtError::Eof)
+    }
+
+    /// First two cells of the value.
+    pub fn try_u64(&self) -> FdtResult<'a, u64> {
+        self.data.clone().take_u64().ok_or(FdtError::Eof)
+    }
+
+    /// Value up to the first NUL.
+    pub fn try_str(&self) -> FdtResult<'a, &'a str> {
+        let data = self.data.remaining();
+        let s =
+            CStr::from_bytes_until_nul(data).map_err(|_| FdtError::FromBytesUntilNull { data })?;
+        s.to_str().map_err(|_| FdtError::Utf8Parse { data })
+    }
+
+    /// # Panics
+    ///
+    /// If the value is shorter than a cell, see [Property::try_u32].
+    #[allow(clippy::expect_used)]
     pub fn u32(&self) -> u32 {
-        self.data.clone().take_u32().unwrap()
+        self.try_u32().expect("property too short for u32")
     }
 
+    /// # Panics
+    ///
+    /// If the value is shorter than two cells, see [Property::try_u64].
+    #[allow(clippy::expect_used)]
     pub fn u64(&self) -> u64 {
-        self.data.clone().take_u64().unwrap()
+        self.try_u64().expect("property too short for u64")
     }
 
+    /// # Panics
+    ///
+    /// If the value is not a NUL terminated UTF-8 string, see
+    /// [Property::try_str].
+    #[allow(clippy::expect_used)]
     pub fn str(&self) -> &'a str {
-        CStr::from_bytes_until_nul(self.data.remaining())
-            .unwrap()
-            .to_str()
-            .unwrap()
+        self.try_str().expect("property is not a string")
+    }
+}
+
+/// Types a property value converts to, see [crate::Node::get].
+///
+/// Sized types check the value length and report [FdtError::SizeMismatch]
+/// rather than reading a prefix of the value.
+pub trait FromProperty<'a>: Sized {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self>;
+
+    /// Result when the node has no such property.
+    fn missing() -> FdtResult<'a, Self> {
+        Err(FdtError::MissingProperty)
+    }
+}
+
+/// The value must be exactly `len` bytes long.
+fn exact<'a>(prop: &Property<'a>, len: usize) -> FdtResult<'a, &'a [u8]> {
+    let data = prop.raw_value();
+    if data.len() != len {
+        return Err(FdtError::SizeMismatch {
+            expected: len,
+            found: data.len(),
+        });
+    }
+    Ok(data)
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Integers stored big-endian in property values, `/bits/ 8`, `16`, `32`
+/// or `64` in devicetree source. Implemented for `u8`, `u16`, `u32` and
+/// `u64` only.
+pub trait BigEndian: sealed::Sealed + Sized {
+    #[doc(hidden)]
+    fn from_be(data: &[u8]) -> Self;
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),*) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl BigEndian for $ty {
+            fn from_be(data: &[u8]) -> Self {
+                let mut bytes = [0; size_of::<$ty>()];
+                bytes.iter_mut().zip(data).for_each(|(b, v)| *b = *v);
+                <$ty>::from_be_bytes(bytes)
+            }
+        }
+
+        impl<'a> FromProperty<'a> for $ty {
+            fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+                exact(prop, size_of::<$ty>()).map(<$ty as BigEndian>::from_be)
+            }
+        }
+    )*};
+}
+
+impl_int!(u8, u16, u32, u64);
+
+/// Array of big-endian integers, such as a `/bits/ 16 <1 2 3>` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Array<'a, T> {
+    data: &'a [u8],
+    _ty: PhantomData<T>,
+}
+
+impl<T: BigEndian> Array<'_, T> {
+    pub fn len(&self) -> usize {
+        self.data
+            .len()
+            .checked_div(size_of::<T>())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl<T: BigEndian> Iterator for Array<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, rest) = self.data.split_at_checked(size_of::<T>())?;
+        self.data = rest;
+        Some(T::from_be(value))
+    }
+}
+
+impl<'a, T: BigEndian> FromProperty<'a> for Array<'a, T> {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+        let data = prop.raw_value();
+        if !data.len().is_multiple_of(size_of::<T>()) {
+            return Err(FdtError::SizeMismatch {
+                expected: data.len().next_multiple_of(size_of::<T>()),
+                found: data.len(),
+            });
+        }
+        Ok(Array {
+            data,
+            _ty: PhantomData,
+        })
+    }
+}
+
+impl<'a, const N: usize> FromProperty<'a> for [u32; N] {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+        let mut cells = Array::<u32>::from_property(prop)?;
+        if cells.len() != N {
+            return Err(FdtError::SizeMismatch {
+                expected: N.saturating_mul(size_of::<u32>()),
+                found: prop.raw_value().len(),
+            });
+        }
+        Ok(core::array::from_fn(|_| cells.next().unwrap_or_default()))
+    }
+}
+
+impl<'a> FromProperty<'a> for Cells<'a> {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+        Array::<u32>::from_property(prop).map(|a| Cells::new(a.data))
+    }
+}
+
+/// `true` when the property is present, whatever its value.
+impl<'a> FromProperty<'a> for bool {
+    fn from_property(_prop: &Property<'a>) -> FdtResult<'a, Self> {
+        Ok(true)
+    }
+
+    fn missing() -> FdtResult<'a, Self> {
+        Ok(false)
+    }
+}
+
+/// A single string, use [StrList] for a string list such as `compatible`.
+impl<'a> FromProperty<'a> for &'a str {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+        let s = prop.try_str()?;
+        exact(prop, s.len().saturating_add(1))?;
+        Ok(s)
+    }
+}
+
+impl<'a> FromProperty<'a> for StrList<'a> {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+        let data = prop.raw_value();
+        let strings = data.strip_suffix(&[0]).unwrap_or_default();
+        if data.last() != Some(&0) || core::str::from_utf8(strings).is_err() {
+            return Err(FdtError::Utf8Parse { data });
+        }
+        Ok(StrList::new(data))
+    }
+}
+
+impl<'a> FromProperty<'a> for Phandle {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+        u32::from_property(prop).map(Phandle::from)
+    }
+}
+
+/// `None` when the property is missing.
+impl<'a, T: FromProperty<'a>> FromProperty<'a> for Option<T> {
+    fn from_property(prop: &Property<'a>) -> FdtResult<'a, Self> {
+        T::from_property(prop).map(Some)
+    }
+
+    fn missing() -> FdtResult<'a, Self> {
+        Ok(None)
     }
 }
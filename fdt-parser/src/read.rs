@@ -3,17 +3,30 @@ use core::ffi::CStr;
 use crate::{
     error::{FdtError, FdtResult},
     property::Property,
-    Fdt, Fdt32, Fdt64, FdtReserveEntry, Token,
+    CellAddress, Fdt, Fdt32, Fdt64, FdtReserveEntry, Token,
 };
 
 #[derive(Clone)]
 pub(crate) struct FdtReader<'a> {
     bytes: &'a [u8],
+    /// Address of the structure block of a blob older than version 16, whose
+    /// property values of 8 bytes or more are 8-byte aligned.
+    legacy_base: Option<usize>,
 }
 
 impl<'a> FdtReader<'a> {
     pub fn new(bytes: &'a [u8]) -> Self {
-        Self { bytes }
+        Self {
+            bytes,
+            legacy_base: None,
+        }
+    }
+
+    /// Read the structure block of a blob older than version 16 starting at
+    /// `struct_base`.
+    pub fn with_legacy_base(mut self, struct_base: usize) -> Self {
+        self.legacy_base = Some(struct_base);
+        self
     }
 
     pub fn take_u32(&mut self) -> Option<u32> {
@@ -28,13 +41,17 @@ impl<'a> FdtReader<'a> {
         Some(fdt64.get())
     }
 
-    pub fn take_by_cell_size(&mut self, cell_size: u8) -> Option<u64> {
-        match cell_size {
-            1 => self.take_u32().map(|s| s as _),
-            2 => self.take_u64(),
-            _ => panic!("invalid cell size {}", cell_size),
+    /// Address or size of `cells` cells, `None` at the end of data or for
+    /// more than [CellAddress::MAX_CELLS] cells.
+    pub fn take_address(&mut self, cells: u8) -> Option<CellAddress> {
+        let mut buf = [0; CellAddress::MAX_CELLS];
+        let buf = buf.get_mut(..cells as usize)?;
+        for cell in buf.iter_mut() {
+            *cell = self.take_u32()?;
         }
+        CellAddress::new(buf)
     }
+
     pub fn skip(&mut self, n_bytes: usize) -> FdtResult<'a> {
         self.bytes = self.bytes.get(n_bytes..).ok_or(FdtError::Eof)?;
         Ok(())
@@ -78,12 +95,24 @@ impl<'a> FdtReader<'a> {
     // }
 
     pub fn take_aligned(&mut self, len: usize) -> Option<&'a [u8]> {
-        let bytes = (len + 3) & !0x3;
+        let bytes = len.checked_add(3)? & !0x3;
         self.take(bytes)
     }
 
+    /// Take a property value of `len` bytes, skipping the padding older blobs
+    /// put before long values.
+    pub fn take_prop_value(&mut self, len: usize) -> Option<&'a [u8]> {
+        if let Some(base) = self.legacy_base {
+            let offset = (self.bytes.as_ptr() as usize).wrapping_sub(base);
+            if len >= 8 && !offset.is_multiple_of(8) {
+                self.skip(4).ok()?;
+            }
+        }
+        self.take_aligned(len)
+    }
+
     pub fn skip_4_aligned(&mut self, len: usize) -> FdtResult<'a> {
-        self.skip((len + 3) & !0x3)
+        self.skip(len.checked_add(3).ok_or(FdtError::Eof)? & !0x3)
     }
 
     pub fn reserved_memory(&mut self) -> Option<FdtReserveEntry> {
@@ -92,20 +121,57 @@ impl<'a> FdtReader<'a> {
         Some(FdtReserveEntry::new(address, size))
     }
 
+    /// Node name, blobs older than version 16 hold the full path of the node
+    /// instead.
     pub fn take_unit_name(&mut self) -> FdtResult<'a, &'a str> {
-        let unit_name = self.peek_str()?;
-        let full_name_len = unit_name.len() + 1;
+        let name = self.peek_str()?;
+        let full_name_len = name.len().saturating_add(1);
         let _ = self.skip_4_aligned(full_name_len);
+        let unit_name = name.rsplit('/').next().unwrap_or(name);
         Ok(if unit_name.is_empty() { "/" } else { unit_name })
     }
 
-    pub fn take_prop(&mut self, fdt: &'a Fdt<'a>) -> Option<Property<'a>> {
+    pub fn skip_prop(&mut self) -> Option<()> {
+        let len = self.take_u32()?;
+        self.take_u32()?;
+        self.take_prop_value(len as _)?;
+        Some(())
+    }
+
+    /// Skip the rest of a node whose `BEGIN_NODE` token was already taken,
+    /// including all of its children and the closing `END_NODE`.
+    pub fn skip_node(&mut self) -> Option<()> {
+        let name = self.peek_str().ok()?;
+        self.skip_4_aligned(name.len().saturating_add(1)).ok()?;
+        self.skip_node_body()
+    }
+
+    /// Like [FdtReader::skip_node], but positioned after the node name.
+    pub fn skip_node_body(&mut self) -> Option<()> {
+        let mut depth = 1usize;
+        while depth > 0 {
+            match self.take_token()? {
+                Token::BeginNode => {
+                    let name = self.peek_str().ok()?;
+                    self.skip_4_aligned(name.len().saturating_add(1)).ok()?;
+                    depth = depth.saturating_add(1);
+                }
+                Token::EndNode => depth = depth.saturating_sub(1),
+                Token::Prop => self.skip_prop()?,
+                Token::Nop => {}
+                _ => return None,
+            }
+        }
+        Some(())
+    }
+
+    pub fn take_prop(&mut self, fdt: &Fdt<'a>) -> Option<Property<'a>> {
         let len = self.take_u32()?;
         let nameoff = self.take_u32()?;
-        let bytes = self.take_aligned(len as _)?;
+        let bytes = self.take_prop_value(len as _)?;
         Some(Property {
             name: fdt.get_str(nameoff as _).unwrap_or("<error>"),
-            data: FdtReader { bytes },
+            data: FdtReader::new(bytes.get(..len as usize).unwrap_or(bytes)),
         })
     }
 
@@ -121,7 +187,7 @@ impl<'a> FdtReader<'a> {
 
     pub fn take_str(&mut self) -> FdtResult<'a, &'a str> {
         let s = self.peek_str()?;
-        let _ = self.skip(s.bytes().len() + 1);
+        let _ = self.skip(s.len().saturating_add(1));
         Ok(s)
     }
 }
@@ -137,10 +203,17 @@ impl<'a> U32Array<'a> {
         }
     }
 
+    /// The next one or two cells as a single value.
+    ///
+    /// # Panics
+    ///
+    /// If no cell is left.
+    #[deprecated(note = "use `CellAddress::new` and `u64::try_from`, which handle up to 4 cells")]
+    #[allow(clippy::expect_used, dead_code)]
     pub fn as_u64(&mut self) -> u64 {
-        let h = self.reader.take_u32().unwrap();
+        let h = self.reader.take_u32().expect("no cell left");
         if let Some(l) = self.reader.take_u32() {
-            ((h as u64) << 32) + l as u64
+            (u64::from(h) << 32) | u64::from(l)
         } else {
             h as _
         }
@@ -173,7 +246,12 @@ impl<'a> Iterator for U32Array2D<'a> {
     type Item = U32Array<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let bytes = self.reader.take(self.row_len * size_of::<u32>())?;
+        if self.row_len == 0 {
+            return None;
+        }
+        let bytes = self
+            .reader
+            .take(self.row_len.checked_mul(size_of::<u32>())?)?;
         Some(U32Array::new(bytes))
     }
 }
@@ -0,0 +1,105 @@
+use crate::{error::*, node::Node, Fdt, Specifier};
+
+/// A node with `#reset-cells`, providing reset lines to other nodes.
+#[derive(Clone)]
+pub struct ResetController<'a> {
+    pub node: Node<'a>,
+}
+
+impl<'a> ResetController<'a> {
+    /// Cells of the specifiers of this controller.
+    pub fn reset_cells(&self) -> FdtResult<'a, usize> {
+        Ok(self.node.get::<u32>("#reset-cells")? as usize)
+    }
+
+    /// `compatible` of the controller, which tells the layout of specifiers
+    /// of more than one cell.
+    pub fn compatibles(&self) -> impl Iterator<Item = &'a str> + 'a {
+        self.node.compatibles()
+    }
+
+    /// The reset line `specifier` selects, like Linux
+    /// `of_reset_simple_xlate`: 0 for a controller with a `#reset-cells` of
+    /// 0, the only cell for one with 1. An error if `specifier` doesn't have
+    /// `#reset-cells` cells, or if they are in a layout of the controller's
+    /// own.
+    pub fn line(&self, specifier: &[u32]) -> FdtResult<'a, u32> {
+        let cells = self.reset_cells()?;
+        if specifier.len() != cells {
+            return Err(FdtError::BadCellSize(specifier.len()));
+        }
+        match *specifier {
+            [] => Ok(0),
+            [line] => Ok(line),
+            _ => Err(FdtError::BadCellSize(cells)),
+        }
+    }
+}
+
+/// One reset line of a consumer, see [Node::resets].
+#[derive(Clone)]
+pub struct ResetSpec<'a> {
+    pub controller: ResetController<'a>,
+    /// First cell of the specifier, the reset line for most controllers. 0
+    /// when the controller's `#reset-cells` is 0. See
+    /// [ResetController::line] for a checked one.
+    pub id: u32,
+    /// All cells after the phandle, in the format of the controller's
+    /// `#reset-cells`.
+    pub specifier: Specifier,
+    /// Matching entry of `reset-names`.
+    pub name: Option<&'a str>,
+}
+
+impl<'a> Node<'a> {
+    /// Reset lines of this consumer, from `resets` and `reset-names`. Null
+    /// entries, [FdtError::NullPhandle], are skipped, keeping their
+    /// `reset-names` slot. Ends with an error on an entry that can't be
+    /// read, such as a dangling phandle.
+    pub fn resets(&self) -> impl Iterator<Item = FdtResult<'a, ResetSpec<'a>>> + 'a {
+        let names = self.entry_names("resets");
+
+        self.parse_phandle_with_args("resets", "#reset-cells")
+            .enumerate()
+            .filter(|(_, reset)| !matches!(reset, Err(FdtError::NullPhandle)))
+            .map(move |(i, reset)| {
+                let reset = reset?;
+                Ok(ResetSpec {
+                    controller: ResetController {
+                        node: reset.provider,
+                    },
+                    id: reset.args.first().copied().unwrap_or_default(),
+                    specifier: reset.args,
+                    name: names.and_then(|names| names.iter().nth(i)),
+                })
+            })
+    }
+
+    /// The reset line named `name` in `reset-names`, which the device can't
+    /// work without.
+    pub fn reset_by_name(&self, name: &str) -> FdtResult<'a, ResetSpec<'a>> {
+        self.optional_reset_by_name(name)?
+            .ok_or(FdtError::NotFound("reset"))
+    }
+
+    /// Like [Node::reset_by_name], for a line the device may lack: `None`
+    /// if it isn't listed, an error only if the list is malformed.
+    pub fn optional_reset_by_name(&self, name: &str) -> FdtResult<'a, Option<ResetSpec<'a>>> {
+        for reset in self.resets() {
+            let reset = reset?;
+            if reset.name == Some(name) {
+                return Ok(Some(reset));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Fdt<'a> {
+    /// Nodes with `#reset-cells`.
+    pub fn reset_controllers(&'a self) -> impl Iterator<Item = ResetController<'a>> + 'a {
+        self.readable_nodes()
+            .filter(|node| node.find_property("#reset-cells").is_some())
+            .map(|node| ResetController { node })
+    }
+}
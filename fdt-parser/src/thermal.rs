@@ -0,0 +1,188 @@
+use crate::{error::*, node::Node, phandle::PhandleArgsIter, value::Cells, Phandle};
+
+/// `THERMAL_NO_LIMIT` of `dt-bindings/thermal/thermal.h`.
+const NO_LIMIT: u32 = u32::MAX;
+
+/// The `/thermal-zones` node, see [crate::Fdt::thermal_zones].
+pub struct ThermalZones<'a> {
+    pub node: Node<'a>,
+}
+
+impl<'a> ThermalZones<'a> {
+    pub fn new(node: Node<'a>) -> Self {
+        ThermalZones { node }
+    }
+
+    pub fn zones(&self) -> impl Iterator<Item = ThermalZone<'a>> + 'a {
+        self.node.children().map(|node| ThermalZone { node })
+    }
+
+    /// The zone whose node is named `name`, such as `cpu-thermal`.
+    pub fn zone(&self, name: &str) -> Option<ThermalZone<'a>> {
+        self.zones().find(|zone| zone.node.name == name)
+    }
+}
+
+/// A thermal zone: the sensors measuring it, its trip points and the
+/// cooling devices acting on them.
+#[derive(Clone)]
+pub struct ThermalZone<'a> {
+    pub node: Node<'a>,
+}
+
+impl<'a> ThermalZone<'a> {
+    pub fn name(&self) -> &'a str {
+        self.node.name
+    }
+
+    /// Milliseconds between checks of the zone.
+    pub fn polling_delay(&self) -> Option<u32> {
+        self.node.get("polling-delay").ok()
+    }
+
+    /// Milliseconds between checks while passive cooling is active.
+    pub fn polling_delay_passive(&self) -> Option<u32> {
+        self.node.get("polling-delay-passive").ok()
+    }
+
+    /// Sensors of `thermal-sensors`, with their `#thermal-sensor-cells`
+    /// specifier, such as the sensor channel.
+    pub fn sensors(&self) -> PhandleArgsIter<'a> {
+        self.node
+            .parse_phandle_with_args("thermal-sensors", "#thermal-sensor-cells")
+    }
+
+    /// `coefficients` of the linear combination of the sensors.
+    pub fn coefficients(&self) -> impl Iterator<Item = i32> + 'a {
+        self.node
+            .get::<Cells>("coefficients")
+            .into_iter()
+            .flat_map(|cells| cells.iter().map(|cell| cell as i32))
+    }
+
+    /// Power in milliwatts the zone can dissipate.
+    pub fn sustainable_power(&self) -> Option<u32> {
+        self.node.get("sustainable-power").ok()
+    }
+
+    /// Children of `trips`.
+    pub fn trips(&self) -> impl Iterator<Item = FdtResult<'a, TripPoint<'a>>> + 'a {
+        self.child("trips")
+            .into_iter()
+            .flat_map(|trips| trips.children())
+            .map(TripPoint::try_from)
+    }
+
+    /// Children of `cooling-maps`.
+    pub fn cooling_maps(&self) -> impl Iterator<Item = CoolingMap<'a>> + 'a {
+        self.child("cooling-maps")
+            .into_iter()
+            .flat_map(|maps| maps.children())
+            .map(|node| CoolingMap { node })
+    }
+
+    fn child(&self, name: &str) -> Option<Node<'a>> {
+        self.node.children().find(|child| child.name == name)
+    }
+}
+
+/// What happens when a zone reaches a [TripPoint].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripType {
+    /// Turn on active cooling, such as a fan.
+    Active,
+    /// Throttle, such as lowering the CPU frequency.
+    Passive,
+    /// Notify, the next step is critical.
+    Hot,
+    /// Shut down.
+    Critical,
+}
+
+/// A temperature at which a zone takes action.
+#[derive(Clone)]
+pub struct TripPoint<'a> {
+    pub node: Node<'a>,
+    /// Millicelsius.
+    pub temperature: i32,
+    /// Millicelsius below `temperature` to fall to before the trip ends.
+    pub hysteresis: u32,
+    pub kind: TripType,
+}
+
+impl<'a> TryFrom<Node<'a>> for TripPoint<'a> {
+    type Error = FdtError<'a>;
+
+    fn try_from(node: Node<'a>) -> Result<Self, Self::Error> {
+        let kind = match node.get::<&str>("type")? {
+            "active" => TripType::Active,
+            "passive" => TripType::Passive,
+            "hot" => TripType::Hot,
+            "critical" => TripType::Critical,
+            _ => return Err(FdtError::NotFound("trip type")),
+        };
+
+        Ok(TripPoint {
+            temperature: node.get::<u32>("temperature")? as i32,
+            hysteresis: node.get("hysteresis")?,
+            kind,
+            node,
+        })
+    }
+}
+
+/// Binding of a trip point to the devices that cool the zone.
+#[derive(Clone)]
+pub struct CoolingMap<'a> {
+    pub node: Node<'a>,
+}
+
+impl<'a> CoolingMap<'a> {
+    /// The trip point of `trip`.
+    pub fn trip(&self) -> FdtResult<'a, TripPoint<'a>> {
+        let phandle: Phandle = self.node.get("trip")?;
+        let node = self
+            .node
+            .fdt
+            .get_node_by_phandle(phandle)
+            .ok_or(FdtError::DanglingPhandle(phandle))?;
+        TripPoint::try_from(node)
+    }
+
+    /// Weight of these devices against the other maps of the zone.
+    pub fn contribution(&self) -> Option<u32> {
+        self.node.get("contribution").ok()
+    }
+
+    /// Devices of `cooling-device`, with the range of cooling states to use.
+    pub fn cooling_devices(&self) -> impl Iterator<Item = FdtResult<'a, CoolingDevice<'a>>> + 'a {
+        self.node
+            .parse_phandle_with_args("cooling-device", "#cooling-cells")
+            .map(|device| {
+                let device = device?;
+                let state = |i: usize| {
+                    let state = device
+                        .args
+                        .get(i)
+                        .copied()
+                        .ok_or(FdtError::BadCellSize(i))?;
+                    Ok(Some(state).filter(|state| *state != NO_LIMIT))
+                };
+                Ok(CoolingDevice {
+                    min_state: state(0)?,
+                    max_state: state(1)?,
+                    node: device.provider,
+                })
+            })
+    }
+}
+
+/// A device a [CoolingMap] uses, such as a CPU whose frequency it lowers.
+#[derive(Clone)]
+pub struct CoolingDevice<'a> {
+    pub node: Node<'a>,
+    /// Lowest cooling state to use, `None` for no limit.
+    pub min_state: Option<u32>,
+    /// Highest cooling state to use, `None` for no limit.
+    pub max_state: Option<u32>,
+}
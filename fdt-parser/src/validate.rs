@@ -0,0 +1,156 @@
+use core::ops::Range;
+
+use crate::{error::*, read::FdtReader, Fdt, Token};
+
+/// Newest blob layout this crate reads.
+pub(crate) const FDT_VERSION: u32 = 17;
+/// Oldest blob layout this crate reads.
+pub(crate) const FDT_FIRST_SUPPORTED_VERSION: u32 = 1;
+
+impl<'a> Fdt<'a> {
+    /// Create a new FDT from raw data, checking the whole blob first.
+    ///
+    /// See [Fdt::validate] for what is checked.
+    pub fn from_bytes_checked(data: &'a [u8]) -> FdtResult<'a, Self> {
+        let fdt = Self::from_bytes(data)?;
+        fdt.validate()?;
+        Ok(fdt)
+    }
+
+    /// Check the blob is well formed, so walking it can't go out of bounds:
+    ///
+    /// - the header and every block fit in `totalsize`, which fits in the
+    ///   data, and the blocks are aligned and don't overlap,
+    /// - `version` and `last_comp_version` are a layout this crate reads,
+    /// - the structure block is a single root node with balanced
+    ///   `BEGIN_NODE`/`END_NODE` tokens, ended by an `END` token,
+    /// - every property name offset points inside the strings block.
+    pub fn validate(&self) -> FdtResult<'a> {
+        let header = &self.header;
+        let header_len = header.len();
+        let total_size = header.totalsize.get() as usize;
+        if total_size > self.data.len() || total_size < header_len {
+            return Err(FdtError::TotalSizeOutOfBounds);
+        }
+
+        let version = header.version.get();
+        let last_comp_version = header.last_comp_version.get();
+        if last_comp_version > FDT_VERSION
+            || version < FDT_FIRST_SUPPORTED_VERSION
+            || version < last_comp_version
+        {
+            return Err(FdtError::UnsupportedVersion {
+                version,
+                last_comp_version,
+            });
+        }
+
+        let in_blob = |range: &Range<usize>| range.start >= header_len && range.end <= total_size;
+
+        // Blobs too old to record a block size get the space up to the next
+        // block.
+        let struct_range = Some(header.struct_range())
+            .filter(in_blob)
+            .ok_or(FdtError::StructBlockOutOfBounds)?;
+        let strings_range = Some(header.strings_range())
+            .filter(in_blob)
+            .ok_or(FdtError::StringsBlockOutOfBounds)?;
+
+        let rsvmap_start = header.off_mem_rsvmap.get() as usize;
+        if !rsvmap_start.is_multiple_of(8)
+            || !struct_range.start.is_multiple_of(4)
+            || !struct_range.len().is_multiple_of(4)
+        {
+            return Err(FdtError::Misaligned);
+        }
+        let rsvmap_range = self.rsvmap_range(rsvmap_start, header_len, total_size)?;
+
+        let overlap = |a: &Range<usize>, b: &Range<usize>| a.start < b.end && b.start < a.end;
+        if overlap(&struct_range, &strings_range)
+            || overlap(&rsvmap_range, &struct_range)
+            || overlap(&rsvmap_range, &strings_range)
+        {
+            return Err(FdtError::BlocksOverlap);
+        }
+
+        self.validate_struct(strings_range.len())
+    }
+
+    /// The memory reservation block, up to and including its terminating
+    /// empty entry.
+    fn rsvmap_range(
+        &self,
+        start: usize,
+        header_len: usize,
+        total_size: usize,
+    ) -> FdtResult<'a, Range<usize>> {
+        let bytes = self
+            .data
+            .get(start..total_size)
+            .filter(|_| start >= header_len)
+            .ok_or(FdtError::MemRsvmapOutOfBounds)?;
+        let mut reader = FdtReader::new(bytes);
+        loop {
+            let entry = reader
+                .reserved_memory()
+                .ok_or(FdtError::MemRsvmapOutOfBounds)?;
+            if entry.address == 0 && entry.size == 0 {
+                break;
+            }
+        }
+        Ok(start..total_size.saturating_sub(reader.remaining().len()))
+    }
+
+    fn validate_struct(&self, strings_len: usize) -> FdtResult<'a> {
+        let mut reader = self.struct_reader(0);
+        let mut depth = 0usize;
+        let mut seen_root = false;
+
+        loop {
+            let offset = self.struct_offset(&reader);
+            if reader.is_empty() {
+                return Err(FdtError::MissingEnd);
+            }
+            let raw = reader.take_u32().ok_or(FdtError::TruncatedStruct)?;
+            let bad_token = FdtError::BadToken { token: raw, offset };
+
+            match Token::from(raw) {
+                Token::BeginNode => {
+                    if depth == 0 && seen_root {
+                        return Err(bad_token);
+                    }
+                    seen_root = true;
+                    depth = depth.checked_add(1).ok_or(FdtError::TooDeep)?;
+                    reader.take_unit_name().map_err(|e| match e {
+                        FdtError::Eof => FdtError::TruncatedStruct,
+                        e => e,
+                    })?;
+                }
+                Token::EndNode => {
+                    depth = depth.checked_sub(1).ok_or(FdtError::UnbalancedNodes)?;
+                }
+                Token::Prop => {
+                    if depth == 0 {
+                        return Err(bad_token);
+                    }
+                    let len = reader.take_u32().ok_or(FdtError::TruncatedStruct)?;
+                    let nameoff = reader.take_u32().ok_or(FdtError::TruncatedStruct)?;
+                    reader
+                        .take_prop_value(len as _)
+                        .ok_or(FdtError::TruncatedStruct)?;
+                    if nameoff as usize >= strings_len || self.get_str(nameoff as _).is_err() {
+                        return Err(FdtError::BadStringOffset(nameoff));
+                    }
+                }
+                Token::Nop => {}
+                Token::End => {
+                    if depth != 0 || !seen_root {
+                        return Err(FdtError::UnbalancedNodes);
+                    }
+                    return Ok(());
+                }
+                Token::Data => return Err(bad_token),
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use core::fmt::{Display, Write};
+
+use crate::{property::Property, Fdt32, Phandle};
+
+/// Value of a [Property], decoded by [Property::value].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyValue<'a> {
+    /// No value, the property is a flag such as `interrupt-controller`.
+    Empty,
+    String(&'a str),
+    /// More than one string.
+    StringList(StrList<'a>),
+    U32Array(Cells<'a>),
+    /// Anything else.
+    Bytes(&'a [u8]),
+    /// Reference to another node, or the phandle of the node itself.
+    Phandle(Phandle),
+}
+
+/// How a property with a well-known name is decoded.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Strings,
+    Cells,
+    Phandle,
+}
+
+fn kind_of(name: &str) -> Option<Kind> {
+    match name {
+        "compatible" | "model" | "status" | "device_type" | "bootargs" | "stdout-path" => {
+            Some(Kind::Strings)
+        }
+        "phandle" | "linux,phandle" | "interrupt-parent" => Some(Kind::Phandle),
+        "reg"
+        | "ranges"
+        | "dma-ranges"
+        | "interrupts"
+        | "interrupts-extended"
+        | "interrupt-map"
+        | "interrupt-map-mask"
+        | "clocks"
+        | "clock-frequency"
+        | "bus-range" => Some(Kind::Cells),
+        _ if name.ends_with("-names") => Some(Kind::Strings),
+        _ if name.starts_with('#') && name.ends_with("-cells") => Some(Kind::Cells),
+        _ => None,
+    }
+}
+
+/// dtc's test for a printable string list: NUL terminated, only printable
+/// characters, and no more NULs than other bytes.
+fn is_strings(data: &[u8]) -> bool {
+    let is_string_byte = |b: &u8| b.is_ascii_graphic() || b" \0\x07\x08\t\n\x0b\x0c\r".contains(b);
+    let nul = data.iter().filter(|b| **b == 0).count();
+    data.last() == Some(&0)
+        && data.iter().all(is_string_byte)
+        && nul <= data.len().saturating_sub(nul)
+}
+
+impl<'a> Property<'a> {
+    /// Decode the value the way dtc guesses types when decompiling, well-known
+    /// properties like `reg`, `compatible`, `*-names` or `phandle` getting
+    /// their usual type when their value allows it.
+    pub fn value(&self) -> PropertyValue<'a> {
+        let data = self.raw_value();
+        if data.is_empty() {
+            return PropertyValue::Empty;
+        }
+        let is_cells = data.len().is_multiple_of(size_of::<u32>());
+
+        let kind = kind_of(self.name).or_else(|| {
+            if is_strings(data) {
+                Some(Kind::Strings)
+            } else if is_cells {
+                Some(Kind::Cells)
+            } else {
+                None
+            }
+        });
+
+        match (kind, data) {
+            (Some(Kind::Phandle), &[a, b, c, d]) => {
+                PropertyValue::Phandle(u32::from_be_bytes([a, b, c, d]).into())
+            }
+            (Some(Kind::Strings), _) if is_strings(data) => {
+                let list = StrList(data);
+                match (list.iter().next(), list.len()) {
+                    (Some(s), 1) => PropertyValue::String(s),
+                    _ => PropertyValue::StringList(list),
+                }
+            }
+            (Some(Kind::Cells | Kind::Phandle), _) if is_cells => {
+                PropertyValue::U32Array(Cells(data))
+            }
+            _ => PropertyValue::Bytes(data),
+        }
+    }
+}
+
+/// NUL terminated strings of a property value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrList<'a>(&'a [u8]);
+
+impl<'a> StrList<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self(data)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + 'a {
+        self.0
+            .strip_suffix(&[0])
+            .unwrap_or(self.0)
+            .split(|b| *b == 0)
+            .map(|s| core::str::from_utf8(s).unwrap_or_default())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().filter(|b| **b == 0).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Big-endian 32-bit cells of a property value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cells<'a>(&'a [u8]);
+
+impl<'a> Cells<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self(data)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + 'a {
+        self.0
+            .chunks_exact(size_of::<u32>())
+            .map(|cell| Fdt32::from(cell).get())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0
+            .len()
+            .checked_div(size_of::<u32>())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Formats the value in devicetree source syntax, such as `"okay"` or
+/// `<0x1 0x2>`.
+impl Display for PropertyValue<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let write_str = |f: &mut core::fmt::Formatter<'_>, s: &str| {
+            f.write_char('"')?;
+            for c in s.chars() {
+                match c {
+                    '"' => f.write_str("\\\"")?,
+                    '\\' => f.write_str("\\\\")?,
+                    '\t' => f.write_str("\\t")?,
+                    '\n' => f.write_str("\\n")?,
+                    '\r' => f.write_str("\\r")?,
+                    c if c.is_ascii_control() => write!(f, "\\x{:02x}", c as u32)?,
+                    c => f.write_char(c)?,
+                }
+            }
+            f.write_char('"')
+        };
+
+        match self {
+            PropertyValue::Empty => Ok(()),
+            PropertyValue::String(s) => write_str(f, s),
+            PropertyValue::StringList(list) => {
+                for (i, s) in list.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_str(f, s)?;
+                }
+                Ok(())
+            }
+            PropertyValue::U32Array(cells) => {
+                f.write_char('<')?;
+                for (i, cell) in cells.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write!(f, "{:#x}", cell)?;
+                }
+                f.write_char('>')
+            }
+            PropertyValue::Bytes(bytes) => {
+                f.write_char('[')?;
+                for (i, b) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write!(f, "{:02x}", b)?;
+                }
+                f.write_char(']')
+            }
+            PropertyValue::Phandle(phandle) => write!(f, "{}", phandle),
+        }
+    }
+}
@@ -143,7 +143,8 @@ mod test {
         let regs = node.reg().unwrap().collect::<Vec<_>>();
         let reg = regs[0];
 
-        assert_eq!(reg.address, 0x7d500000);
+        assert_eq!(reg.address, 0xfd500000);
+        assert_eq!(reg.child_bus_address, 0x7d500000);
         assert_eq!(reg.size, Some(0x9310));
     }
 
@@ -185,4 +186,1920 @@ mod test {
             assert_eq!(range, want[i]);
         }
     }
+
+    #[test]
+    fn test_children() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let mux = fdt.find_nodes("/soc/i2c0mux").next().unwrap();
+        let names = mux.children().map(|n| n.name).collect::<Vec<_>>();
+        assert_eq!(names, ["i2c@0", "i2c@1"]);
+
+        let soc = fdt.find_nodes("/soc").next().unwrap();
+        let uart = soc
+            .children()
+            .find(|n| n.name == "serial@7e215040")
+            .unwrap();
+        assert_eq!(uart.level, soc.level + 1);
+        assert_eq!(uart.reg().unwrap().next().unwrap().address, 0xfe215040);
+    }
+
+    #[test]
+    fn test_descendants() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let soc = fdt.find_nodes("/soc").next().unwrap();
+        let names = soc
+            .descendants()
+            .map(|n| n.unwrap().name)
+            .collect::<Vec<_>>();
+        let mux = names.iter().position(|n| *n == "i2c0mux").unwrap();
+        assert_eq!(names[mux + 1..mux + 3], ["i2c@0", "i2c@1"]);
+
+        let all = fdt.all_nodes().count();
+        let root = fdt.all_nodes().next().unwrap().unwrap();
+        assert_eq!(root.descendants().count(), all - 1);
+    }
+
+    #[test]
+    fn test_parent() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let node = fdt.find_nodes("/soc/i2c0mux/i2c@1").next().unwrap();
+        let parent = node.parent().unwrap();
+        assert_eq!(parent.name, "i2c0mux");
+        assert_eq!(parent.level, node.level - 1);
+
+        let root = fdt.all_nodes().next().unwrap().unwrap();
+        assert!(root.parent().is_none());
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let node = fdt.find_nodes("/soc/i2c0mux/i2c@1").next().unwrap();
+        let names = node.ancestors().map(|n| n.name).collect::<Vec<_>>();
+        assert_eq!(names, ["i2c0mux", "soc", "/"]);
+    }
+
+    #[test]
+    fn test_next_sibling() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let aux = fdt.find_nodes("/soc/aux@7e215000").next().unwrap();
+        let uart = aux.next_sibling().unwrap();
+        assert_eq!(uart.name, "serial@7e215040");
+        assert_eq!(uart.reg().unwrap().next().unwrap().address, 0xfe215040);
+
+        let spi = uart.next_sibling().unwrap();
+        assert_eq!(spi.name, "spi@7e215080");
+
+        let mux = fdt.find_nodes("/soc/i2c0mux/i2c@1").next().unwrap();
+        assert!(mux.next_sibling().is_none());
+    }
+
+    #[test]
+    fn test_path() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let node = fdt
+            .find_compatible(&["brcm,bcm2835-aux-uart"])
+            .next()
+            .unwrap();
+        let mut path = String::new();
+        node.path(&mut path).unwrap();
+        assert_eq!(path, "/soc/serial@7e215040");
+        assert_eq!(fdt.path_of(&node).to_string(), "/soc/serial@7e215040");
+
+        let root = fdt.all_nodes().next().unwrap().unwrap();
+        assert_eq!(fdt.path_of(&root).to_string(), "/");
+
+        for alias in ["i2c10", "bluetooth", "mmc0"] {
+            let node = fdt.find_nodes(alias).next().unwrap();
+            let want = fdt.find_aliase(alias).unwrap();
+            assert_eq!(fdt.path_of(&node).to_string(), want);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_path_string() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let node = fdt.find_nodes("/soc/i2c0mux/i2c@1").next().unwrap();
+        assert_eq!(node.path_string().unwrap(), "/soc/i2c0mux/i2c@1");
+    }
+
+    #[test]
+    fn test_node_at() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let node = fdt.find_nodes("/soc/serial@7e215040").next().unwrap();
+        let offset = node.offset();
+
+        let again = fdt.node_at(offset).unwrap();
+        assert!(again == node);
+        assert_eq!(again.name, "serial@7e215040");
+        assert_eq!(again.level, node.level);
+        assert_eq!(again.reg().unwrap().next().unwrap().address, 0xfe215040);
+        assert_eq!(
+            again.interrupt_parent().unwrap().node.name,
+            node.interrupt_parent().unwrap().node.name
+        );
+
+        let root = fdt.all_nodes().next().unwrap().unwrap();
+        assert_eq!(fdt.node_at(root.offset()).unwrap().name, "/");
+        assert!(fdt
+            .node_at(NodeOffset::from(offset.as_usize() + 4))
+            .is_none());
+        assert!(fdt
+            .node_at(NodeOffset::from(offset.as_usize() + 1))
+            .is_none());
+        assert!(fdt.node_at(NodeOffset::from(usize::MAX)).is_none());
+
+        let soc = node.parent().unwrap();
+        let child = soc.child_at(offset).unwrap();
+        assert!(child == node);
+        assert_eq!(child.reg().unwrap().next().unwrap().address, 0xfe215040);
+        assert!(soc.child_at(soc.offset()).is_none());
+    }
+
+    #[test]
+    fn test_node_eq_hash() {
+        use std::collections::HashMap;
+
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let mut map = HashMap::new();
+        for node in fdt.find_nodes("/soc/serial") {
+            map.insert(node.clone(), node.name);
+        }
+        let uart = fdt.find_nodes("serial0").next().unwrap();
+        assert_eq!(map[&uart], "serial@7e215040");
+
+        let other = fdt.find_nodes("serial1").next().unwrap();
+        assert!(uart != other);
+    }
+
+    #[test]
+    fn test_index() {
+        let plain = Fdt::from_bytes(TEST_FDT).unwrap();
+        let mut buffer = vec![IndexEntry::default(); FdtIndex::required_len(&plain)];
+        let index = FdtIndex::new_in(&plain, &mut buffer).unwrap();
+        assert_eq!(index.len(), plain.all_nodes().count());
+
+        let fdt = Fdt::from_bytes(TEST_FDT)
+            .unwrap()
+            .with_index(&index)
+            .unwrap();
+
+        for node in plain.all_nodes().map(Result::unwrap) {
+            if let Some(phandle) = node.phandle() {
+                let found = fdt.get_node_by_phandle(phandle).unwrap();
+                assert!(found.offset() == node.offset());
+                assert_eq!(found.level, node.level);
+            }
+        }
+
+        let path = "/soc/serial@7e215040";
+        let node = fdt.find_nodes(path).next().unwrap();
+        assert_eq!(fdt.path_of(&node).to_string(), path);
+        assert_eq!(node.reg().unwrap().next().unwrap().address, 0xfe215040);
+        assert_eq!(index.find_path(path), Some(node.offset()));
+        assert_eq!(fdt.find_nodes("serial0").next().unwrap().name, node.name);
+        assert_eq!(fdt.find_nodes("/").next().unwrap().name, "/");
+
+        for path in ["/soc/serial", "/soc/i2c@1", "/soc/i2c0mux/i2c", "/cpus/cpu"] {
+            let want = plain
+                .find_nodes(path)
+                .map(|n| n.offset())
+                .collect::<Vec<_>>();
+            let got = fdt.find_nodes(path).map(|n| n.offset()).collect::<Vec<_>>();
+            assert_eq!(got, want, "{path}");
+        }
+        assert!(plain.find_nodes("/soc/i2c@1").next().is_none());
+        assert_eq!(plain.find_nodes("/soc/i2c0mux/i2c").count(), 2);
+
+        let caps = &["arm,pl011", "brcm,bcm2835-aux-uart", "arm,primecell"];
+        let want = plain
+            .find_compatible(caps)
+            .map(|n| n.offset())
+            .collect::<Vec<_>>();
+        let got = fdt
+            .find_compatible(caps)
+            .map(|n| n.offset())
+            .collect::<Vec<_>>();
+        assert_eq!(got, want);
+
+        let i2c = fdt.find_nodes("/soc/i2c0mux/i2c@1").next().unwrap();
+        let names = i2c.ancestors().map(|n| n.name).collect::<Vec<_>>();
+        assert_eq!(names, ["i2c0mux", "soc", "/"]);
+    }
+
+    #[test]
+    fn test_index_errors() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let mut buffer = vec![IndexEntry::default(); 8];
+        assert!(matches!(
+            FdtIndex::new_in(&fdt, &mut buffer),
+            Err(FdtError::BufferTooSmall)
+        ));
+
+        let other = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let mut buffer = vec![IndexEntry::default(); FdtIndex::required_len(&other)];
+        let index = FdtIndex::new_in(&other, &mut buffer).unwrap();
+        assert!(matches!(
+            fdt.with_index(&index),
+            Err(FdtError::IndexMismatch)
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_index_alloc() {
+        let fdt = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let index = FdtIndex::new(&fdt).unwrap();
+        let fdt = fdt.clone().with_index(&index).unwrap();
+        let pci = fdt
+            .find_compatible(&["pci-host-ecam-generic"])
+            .next()
+            .unwrap();
+        assert_eq!(pci.into_pci().unwrap().ranges().unwrap().count(), 3);
+    }
+
+    /// A blob made of `depth` nodes nested in each other.
+    fn nested_fdt(depth: usize) -> Vec<u8> {
+        let mut dt_struct = Vec::new();
+        for i in 0..depth {
+            dt_struct.extend_from_slice(&1u32.to_be_bytes());
+            let name = if i == 0 { "" } else { "n" };
+            let mut name = name.as_bytes().to_vec();
+            name.push(0);
+            name.resize(name.len().div_ceil(4) * 4, 0);
+            dt_struct.extend_from_slice(&name);
+        }
+        for _ in 0..depth {
+            dt_struct.extend_from_slice(&2u32.to_be_bytes());
+        }
+        dt_struct.extend_from_slice(&9u32.to_be_bytes());
+
+        let off_struct = 40 + 16;
+        let off_strings = off_struct + dt_struct.len();
+        let header = [
+            0xd00dfeed,
+            off_strings as u32,
+            off_struct as u32,
+            off_strings as u32,
+            40,
+            17,
+            16,
+            0,
+            0,
+            dt_struct.len() as u32,
+        ];
+        let mut blob = header
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect::<Vec<_>>();
+        blob.extend_from_slice(&[0; 16]);
+        blob.extend_from_slice(&dt_struct);
+        blob
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let data = nested_fdt(40);
+        let fdt = Fdt::from_bytes(&data).unwrap();
+
+        let all = fdt.all_nodes().collect::<Vec<_>>();
+        assert_eq!(all.len(), DEFAULT_DEPTH + 1);
+        assert!(all[..DEFAULT_DEPTH].iter().all(Result::is_ok));
+        assert!(matches!(all[DEFAULT_DEPTH], Err(FdtError::TooDeep)));
+        let root = all[0].as_ref().unwrap();
+        let descendants = root.descendants().collect::<Vec<_>>();
+        assert_eq!(descendants.len(), DEFAULT_DEPTH);
+        assert!(matches!(descendants.last(), Some(Err(FdtError::TooDeep))));
+
+        // Lookups step from node to node and reach below the limit
+        let path = "/n".repeat(39);
+        let deepest = fdt.find_nodes(&path).next().unwrap();
+        assert_eq!(deepest.level, 40);
+        assert_eq!(deepest.parent().unwrap().level, 39);
+        assert_eq!(deepest.parent().unwrap().children().count(), 1);
+
+        let last = fdt.try_all_nodes::<DEFAULT_DEPTH>().last().unwrap();
+        assert!(matches!(last, Err(FdtError::TooDeep)));
+
+        let nodes = fdt
+            .try_all_nodes::<64>()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(nodes.len(), 40);
+
+        let deepest = fdt.node_at(nodes[39].offset()).unwrap();
+        assert_eq!(deepest.level, 40);
+        assert_eq!(deepest.parent().unwrap().level, 39);
+        assert_eq!(fdt.path_of(&deepest).to_string(), "/n".repeat(39));
+
+        let mut buffer = vec![IndexEntry::default(); FdtIndex::required_len(&fdt)];
+        let index = FdtIndex::new_in(&fdt, &mut buffer).unwrap();
+        assert_eq!(index.len(), 40);
+        let path = "/n".repeat(39);
+        assert_eq!(index.find_path(&path), Some(deepest.offset()));
+    }
+
+    #[test]
+    fn test_index_unbalanced() {
+        let mut data = nested_fdt(2);
+        // END_NODE in place of the BEGIN_NODE of the root.
+        data[56..60].copy_from_slice(&2u32.to_be_bytes());
+        let fdt = Fdt::from_bytes(&data).unwrap();
+
+        let mut buffer = vec![IndexEntry::default(); 4];
+        assert_eq!(FdtIndex::required_len(&fdt), 0);
+        assert!(matches!(
+            FdtIndex::new_in(&fdt, &mut buffer),
+            Err(FdtError::UnbalancedNodes)
+        ));
+    }
+
+    /// `TEST_FDT` with the big-endian word at `offset` replaced.
+    fn patched(offset: usize, value: u32) -> Vec<u8> {
+        let mut data = TEST_FDT.to_vec();
+        data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        data
+    }
+
+    fn header_field(index: usize) -> u32 {
+        u32::from_be_bytes(TEST_FDT[index * 4..index * 4 + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_validate() {
+        Fdt::from_bytes_checked(TEST_FDT).unwrap();
+        Fdt::from_bytes_checked(TEST_PHYTIUM_FDT).unwrap();
+
+        let check = |data: Vec<u8>| {
+            let err = Fdt::from_bytes(&data).unwrap().validate().unwrap_err();
+            format!("{:?}", err)
+        };
+        let off_struct = header_field(2) as usize;
+        let size_struct = header_field(9);
+
+        assert_eq!(check(patched(4, u32::MAX)), "TotalSizeOutOfBounds");
+        assert_eq!(check(patched(8, 0x1000_0000)), "StructBlockOutOfBounds");
+        assert_eq!(check(patched(32, 0x1000_0000)), "StringsBlockOutOfBounds");
+        assert_eq!(check(patched(12, off_struct as u32)), "BlocksOverlap");
+        assert_eq!(check(patched(16, 4)), "Misaligned");
+        assert_eq!(
+            check(patched(16, (header_field(1) - 8) & !7)),
+            "MemRsvmapOutOfBounds"
+        );
+        assert_eq!(
+            check(patched(24, 18)),
+            "UnsupportedVersion { version: 17, last_comp_version: 18 }"
+        );
+        assert_eq!(check(patched(36, size_struct - 4)), "MissingEnd");
+        assert_eq!(
+            check(patched(off_struct + size_struct as usize - 8, 0x4)),
+            "UnbalancedNodes"
+        );
+        // the root has an empty name, its first property follows
+        assert_eq!(
+            check(patched(off_struct + 8, 0x7)),
+            "BadToken { token: 7, offset: 8 }"
+        );
+        assert_eq!(
+            check(patched(off_struct + 16, 0xffff)),
+            "BadStringOffset(65535)"
+        );
+    }
+
+    #[test]
+    fn test_fallible() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let gic = fdt.find_compatible(&["arm,gic-400"]).next().unwrap();
+        let prop = gic.find_property("interrupt-controller").unwrap();
+        assert!(matches!(prop.try_u32(), Err(FdtError::Eof)));
+        assert!(matches!(prop.try_u64(), Err(FdtError::Eof)));
+        assert!(prop.try_str().is_err());
+        assert_eq!(
+            gic.find_property("compatible").unwrap().try_str().unwrap(),
+            "arm,gic-400"
+        );
+
+        let root = fdt.all_nodes().next().unwrap().unwrap();
+        assert!(matches!(root.try_reg(), Err(FdtError::NotFound("reg"))));
+        assert!(root.reg().is_none());
+        assert_eq!(gic.try_reg().unwrap().count(), gic.reg().unwrap().count());
+
+        let node = fdt.find_compatible(&["brcm,bcm2711-hdmi0"]).next().unwrap();
+        let clocks = node.try_clocks().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(clocks.len(), node.clocks().count());
+    }
+
+    #[test]
+    fn test_corrupt_no_panic() {
+        let off_struct = header_field(2) as usize;
+        let size_struct = header_field(9) as usize;
+        for offset in (off_struct..off_struct + size_struct).step_by(4 * 499) {
+            for value in [0, 3, u32::MAX] {
+                let data = patched(offset, value);
+                let fdt = Fdt::from_bytes(&data).unwrap();
+                let _ = fdt.validate();
+                for node in fdt.all_nodes().flatten() {
+                    let _ = node.try_reg().map(|r| r.count());
+                    let _ = node.compatibles().count();
+                    let _ = node.status();
+                    let _ = node.propertys().count();
+                }
+            }
+        }
+    }
+
+    /// `TEST_FDT` re-encoded in the layout of an older `version`: a shorter
+    /// header, and before version 16 full paths as node names, 8-byte aligned
+    /// long property values and `linux,phandle` instead of `phandle`.
+    fn legacy_fdt(version: u32) -> Vec<u8> {
+        let word =
+            |data: &[u8], at: usize| u32::from_be_bytes(data[at..at + 4].try_into().unwrap());
+        let pad =
+            |out: &mut Vec<u8>, align: usize| out.resize(out.len().div_ceil(align) * align, 0);
+        let off_struct = header_field(2) as usize;
+        let off_strings = header_field(3) as usize;
+        let off_rsvmap = header_field(4) as usize;
+        let src = &TEST_FDT[off_struct..off_struct + header_field(9) as usize];
+        let mut strings = TEST_FDT[off_strings..off_strings + header_field(8) as usize].to_vec();
+        let phandle = strings.windows(8).position(|w| w == b"phandle\0").unwrap() as u32;
+        let linux_phandle = strings.len() as u32;
+        strings.extend_from_slice(b"linux,phandle\0");
+
+        let mut dt_struct = Vec::new();
+        let mut path = Vec::new();
+        let mut at = 0;
+        loop {
+            let token = word(src, at);
+            at += 4;
+            dt_struct.extend_from_slice(&token.to_be_bytes());
+            match token {
+                1 => {
+                    let len = src[at..].iter().position(|b| *b == 0).unwrap();
+                    path.push(std::str::from_utf8(&src[at..at + len]).unwrap());
+                    at = (at + len + 4) & !3;
+                    let name = match (version < 16, path.len()) {
+                        (true, 1) => "/".to_string(),
+                        (true, _) => path.join("/"),
+                        (false, _) => path.last().unwrap().to_string(),
+                    };
+                    dt_struct.extend_from_slice(name.as_bytes());
+                    dt_struct.push(0);
+                    pad(&mut dt_struct, 4);
+                }
+                2 => {
+                    path.pop();
+                }
+                3 => {
+                    let len = word(src, at) as usize;
+                    let mut nameoff = word(src, at + 4);
+                    if version < 16 && nameoff == phandle {
+                        nameoff = linux_phandle;
+                    }
+                    dt_struct.extend_from_slice(&(len as u32).to_be_bytes());
+                    dt_struct.extend_from_slice(&nameoff.to_be_bytes());
+                    if version < 16 && len >= 8 {
+                        pad(&mut dt_struct, 8);
+                    }
+                    dt_struct.extend_from_slice(&src[at + 8..at + 8 + len]);
+                    pad(&mut dt_struct, 4);
+                    at = (at + 8 + len + 3) & !3;
+                }
+                9 => break,
+                _ => {}
+            }
+        }
+
+        let rsvmap = &TEST_FDT[off_rsvmap..off_struct];
+        let header_len = match version {
+            1 => 28,
+            2 => 32,
+            3..=16 => 36,
+            _ => 40,
+        };
+        let new_rsvmap = (header_len + 7) & !7;
+        let new_struct = new_rsvmap + rsvmap.len();
+        let new_strings = new_struct + dt_struct.len();
+        let header = [
+            0xd00dfeed,
+            (new_strings + strings.len()) as u32,
+            new_struct as u32,
+            new_strings as u32,
+            new_rsvmap as u32,
+            version,
+            if version < 16 { 1 } else { 16 },
+            0,
+            strings.len() as u32,
+            dt_struct.len() as u32,
+        ];
+        let mut blob = header
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .take(header_len)
+            .collect::<Vec<_>>();
+        pad(&mut blob, 8);
+        blob.extend_from_slice(rsvmap);
+        blob.extend_from_slice(&dt_struct);
+        blob.extend_from_slice(&strings);
+        blob
+    }
+
+    #[test]
+    fn test_legacy_versions() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let expected = fdt
+            .all_nodes()
+            .map(Result::unwrap)
+            .map(|node| {
+                let props = node
+                    .propertys()
+                    .map(|p| (p.name.to_string(), p.raw_value().to_vec()))
+                    .collect::<Vec<_>>();
+                (node.level, node.name().to_string(), props)
+            })
+            .collect::<Vec<_>>();
+
+        for version in [1, 2, 3, 16] {
+            let data = legacy_fdt(version);
+            let legacy = Fdt::from_bytes_checked(&data).unwrap();
+            assert_eq!(legacy.version(), version as usize);
+
+            let nodes = legacy
+                .all_nodes()
+                .map(Result::unwrap)
+                .map(|node| {
+                    let props = node
+                        .propertys()
+                        .map(|p| {
+                            let name = match p.name {
+                                "linux,phandle" => "phandle",
+                                name => name,
+                            };
+                            (name.to_string(), p.raw_value().to_vec())
+                        })
+                        .collect::<Vec<_>>();
+                    (node.level, node.name().to_string(), props)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(nodes, expected, "version {version}");
+
+            let uart = legacy.find_nodes("/soc/serial@7e201000").next().unwrap();
+            assert_eq!(legacy.path_of(&uart).to_string(), "/soc/serial@7e201000");
+            let irq = uart.interrupt_parent().unwrap();
+            assert_eq!(irq.node.name(), "interrupt-controller@40041000");
+            assert_eq!(
+                legacy.memory_reservation_block().count(),
+                fdt.memory_reservation_block().count()
+            );
+
+            let mut buffer = vec![IndexEntry::default(); FdtIndex::required_len(&legacy)];
+            let index = FdtIndex::new_in(&legacy, &mut buffer).unwrap();
+            let phandle = irq.node.phandle().unwrap();
+            assert_eq!(index.find_phandle(phandle), Some(irq.node.offset()));
+        }
+    }
+
+    #[test]
+    fn test_property_value() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let value = |path: &str, name: &str| {
+            let node = fdt.find_nodes(path).next().unwrap();
+            let prop = node.find_property(name).unwrap();
+            format!("{}", prop.value())
+        };
+
+        assert_eq!(
+            value("/", "compatible"),
+            r#""raspberrypi,4-model-b", "brcm,bcm2711""#
+        );
+        assert_eq!(value("/", "model"), r#""Raspberry Pi 4 Model B""#);
+        assert_eq!(value("/", "#address-cells"), "<0x2>");
+        assert_eq!(value("/", "interrupt-parent"), "<0x1>");
+        assert_eq!(value("/soc/serial@7e201000", "reg"), "<0x7e201000 0x200>");
+        assert_eq!(value("/soc/serial@7e201000", "status"), r#""okay""#);
+        assert_eq!(
+            value("/soc/serial@7e201000", "clock-names"),
+            r#""uartclk", "apb_pclk""#
+        );
+        assert_eq!(value("/aliases", "serial0"), r#""/soc/serial@7e215040""#);
+
+        let gic = fdt.find_compatible(&["arm,gic-400"]).next().unwrap();
+        let prop = gic.find_property("interrupt-controller").unwrap();
+        assert_eq!(prop.value(), PropertyValue::Empty);
+        assert!(matches!(
+            gic.find_property("phandle").unwrap().value(),
+            PropertyValue::Phandle(p) if Some(p) == gic.phandle()
+        ));
+
+        let root = fdt.find_nodes("/").next().unwrap();
+        match root.find_property("compatible").unwrap().value() {
+            PropertyValue::StringList(list) => {
+                assert_eq!(list.len(), 2);
+                assert_eq!(list.iter().last(), Some("brcm,bcm2711"));
+            }
+            _ => panic!("compatible is a string list"),
+        }
+
+        for node in fdt.all_nodes().map(Result::unwrap) {
+            for prop in node.propertys() {
+                let shown = prop.value().to_string();
+                match prop.value() {
+                    PropertyValue::U32Array(cells) => {
+                        assert_eq!(cells.len() * 4, prop.raw_value().len());
+                    }
+                    PropertyValue::Bytes(bytes) => assert_eq!(bytes, prop.raw_value()),
+                    PropertyValue::Empty => assert!(shown.is_empty()),
+                    _ => assert!(!shown.is_empty()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_get() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let root = fdt.find_nodes("/").next().unwrap();
+        let uart = fdt.find_nodes("/soc/serial@7e201000").next().unwrap();
+
+        assert_eq!(root.get::<u32>("#address-cells").unwrap(), 2);
+        assert_eq!(
+            root.get::<Phandle>("interrupt-parent").unwrap(),
+            Phandle::from(1)
+        );
+        assert_eq!(root.get::<&str>("model").unwrap(), "Raspberry Pi 4 Model B");
+        assert_eq!(uart.get::<[u32; 2]>("reg").unwrap(), [0x7e201000, 0x200]);
+        assert_eq!(
+            uart.get::<Array<u16>>("reg").unwrap().collect::<Vec<_>>(),
+            [0x7e20, 0x1000, 0, 0x200]
+        );
+        assert_eq!(uart.get::<Array<u8>>("reg").unwrap().len(), 8);
+        assert_eq!(uart.get::<Cells>("reg").unwrap().len(), 2);
+        assert_eq!(
+            root.get::<StrList>("compatible")
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            ["raspberrypi,4-model-b", "brcm,bcm2711"]
+        );
+
+        assert!(matches!(
+            uart.get::<u32>("reg"),
+            Err(FdtError::SizeMismatch {
+                expected: 4,
+                found: 8
+            })
+        ));
+        assert!(matches!(
+            uart.get::<u64>("reg").map(|v| v == 0x7e201000_00000200),
+            Ok(true)
+        ));
+        assert!(matches!(
+            uart.get::<[u32; 3]>("reg"),
+            Err(FdtError::SizeMismatch {
+                expected: 12,
+                found: 8
+            })
+        ));
+        assert!(matches!(
+            root.get::<Array<u64>>("compatible"),
+            Err(FdtError::SizeMismatch { .. })
+        ));
+        assert!(matches!(
+            root.get::<&str>("compatible"),
+            Err(FdtError::SizeMismatch { .. })
+        ));
+        assert!(matches!(
+            root.get::<u32>("no-such-property"),
+            Err(FdtError::MissingProperty)
+        ));
+
+        let gic = fdt.find_compatible(&["arm,gic-400"]).next().unwrap();
+        assert!(gic.get::<bool>("interrupt-controller").unwrap());
+        assert!(!gic.get::<bool>("dma-coherent").unwrap());
+        assert_eq!(gic.get::<Option<u32>>("#interrupt-cells").unwrap(), Some(3));
+        assert_eq!(gic.get::<Option<u32>>("#clock-cells").unwrap(), None);
+    }
+
+    #[test]
+    fn test_cell_address() {
+        let wide = CellAddress::new(&[0x8200_0000, 0x1, 0x2000]).unwrap();
+        assert_eq!(wide.cells(), 3);
+        assert_eq!(wide.cell(0), Some(0x8200_0000));
+        assert_eq!(wide.cell(2), Some(0x2000));
+        assert_eq!(wide.cell(3), None);
+        assert_eq!(wide.as_u128(), 0x8200_0000_0000_0001_0000_2000);
+        assert!(matches!(
+            u64::try_from(wide),
+            Err(FdtError::AddressOverflow)
+        ));
+        assert!(CellAddress::new(&[0; 5]).is_none());
+
+        let base = CellAddress::from(0x1000u64);
+        let size = CellAddress::from(0x100u32);
+        let addr = CellAddress::new(&[0, 0x1080]).unwrap();
+        assert_eq!(addr, 0x1080);
+        assert!(base < addr);
+        assert_eq!(addr.offset_in(base, size), Some(CellAddress::from(0x80u32)));
+        assert_eq!(base.offset_in(addr, size), None);
+        assert_eq!(CellAddress::from(0x1100u32).offset_in(base, size), None);
+        assert_eq!(
+            u64::try_from(addr.checked_add(size).unwrap()).unwrap(),
+            0x1180
+        );
+        assert_eq!(CellAddress::from(u32::MAX).checked_add(size), None);
+        assert_eq!(CellAddress::from(u64::MAX).checked_add(size), None);
+        assert_eq!(base.checked_sub(addr), None);
+
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let usb = fdt
+            .find_nodes("/scb/pcie@7d500000/pci@0,0/usb@0,0")
+            .next()
+            .unwrap();
+        let reg = usb.try_reg().unwrap().next().unwrap();
+        assert_eq!(reg.child_bus_address.cells(), 3);
+        assert_eq!(reg.address, 0);
+        assert_eq!(reg.size, Some(0));
+    }
+
+    /// Builds a blob node by node, for trees the sample blobs don't have.
+    #[derive(Default)]
+    struct Builder {
+        dt_struct: Vec<u8>,
+        strings: Vec<u8>,
+    }
+
+    impl Builder {
+        fn token(&mut self, token: u32) -> &mut Self {
+            self.dt_struct.extend_from_slice(&token.to_be_bytes());
+            self
+        }
+
+        fn begin(&mut self, name: &str) -> &mut Self {
+            self.token(1);
+            self.dt_struct.extend_from_slice(name.as_bytes());
+            self.dt_struct.push(0);
+            self.dt_struct
+                .resize(self.dt_struct.len().div_ceil(4) * 4, 0);
+            self
+        }
+
+        fn end(&mut self) -> &mut Self {
+            self.token(2)
+        }
+
+        fn prop(&mut self, name: &str, value: &[u8]) -> &mut Self {
+            let mut key = name.as_bytes().to_vec();
+            key.push(0);
+            let nameoff = match self.strings.windows(key.len()).position(|w| w == key) {
+                Some(offset) => offset,
+                None => {
+                    self.strings.extend_from_slice(&key);
+                    self.strings.len() - key.len()
+                }
+            };
+            self.token(3)
+                .token(value.len() as u32)
+                .token(nameoff as u32);
+            self.dt_struct.extend_from_slice(value);
+            self.dt_struct
+                .resize(self.dt_struct.len().div_ceil(4) * 4, 0);
+            self
+        }
+
+        fn cells(&mut self, name: &str, cells: &[u32]) -> &mut Self {
+            let value = cells
+                .iter()
+                .flat_map(|c| c.to_be_bytes())
+                .collect::<Vec<_>>();
+            self.prop(name, &value)
+        }
+
+        fn string(&mut self, name: &str, value: &str) -> &mut Self {
+            self.prop(name, format!("{value}\0").as_bytes())
+        }
+
+        fn build(&mut self) -> Vec<u8> {
+            self.token(9);
+            let off_struct = 40 + 16;
+            let off_strings = off_struct + self.dt_struct.len();
+            let header = [
+                0xd00dfeed,
+                (off_strings + self.strings.len()) as u32,
+                off_struct as u32,
+                off_strings as u32,
+                40,
+                17,
+                16,
+                0,
+                self.strings.len() as u32,
+                self.dt_struct.len() as u32,
+            ];
+            let mut blob = header
+                .iter()
+                .flat_map(|v| v.to_be_bytes())
+                .collect::<Vec<_>>();
+            blob.extend_from_slice(&[0; 16]);
+            blob.extend_from_slice(&self.dt_struct);
+            blob.extend_from_slice(&self.strings);
+            blob
+        }
+    }
+
+    #[test]
+    fn test_translate_address() {
+        let data = Builder::default()
+            .begin("")
+            .cells("#address-cells", &[1])
+            .cells("#size-cells", &[1])
+            .begin("bus1")
+            .string("compatible", "simple-bus")
+            .cells("#address-cells", &[1])
+            .cells("#size-cells", &[1])
+            .cells("ranges", &[0x0, 0x8000_0000, 0x10000])
+            .begin("bus2")
+            .cells("#address-cells", &[2])
+            .cells("#size-cells", &[1])
+            .cells("ranges", &[0x1, 0x0, 0x1000, 0x1000])
+            .begin("dev@1,100")
+            .cells("reg", &[0x1, 0x100, 0x10, 0x2, 0x0, 0x10])
+            .end()
+            .end()
+            .begin("identity")
+            .cells("#address-cells", &[1])
+            .cells("#size-cells", &[1])
+            .prop("ranges", &[])
+            .begin("dev@20")
+            .cells("reg", &[0x20, 0x4])
+            .end()
+            .end()
+            .begin("local")
+            .cells("#address-cells", &[1])
+            .cells("#size-cells", &[0])
+            .begin("dev@3")
+            .cells("reg", &[0x3])
+            .end()
+            .end()
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes_checked(&data).unwrap();
+        let node = |path| fdt.find_nodes(path).next().unwrap();
+
+        let dev = node("/bus1/bus2/dev@1,100");
+        let regs = dev.reg().unwrap().collect::<Vec<_>>();
+        assert_eq!(regs[0].address, 0x8000_1100);
+        assert_eq!(regs[0].cpu_address, Some(CellAddress::from(0x8000_1100u32)));
+        assert_eq!(regs[0].child_bus_address, 0x1_0000_0100);
+        assert_eq!(regs[1].cpu_address, None);
+        assert!(matches!(
+            dev.translate_address(regs[1].child_bus_address),
+            Err(FdtError::NoMatchingRange)
+        ));
+
+        let reg = node("/bus1/identity/dev@20").reg().unwrap().next().unwrap();
+        assert_eq!(reg.cpu_address, Some(CellAddress::from(0x8000_0020u32)));
+
+        let dev = node("/bus1/local/dev@3");
+        let reg = dev.reg().unwrap().next().unwrap();
+        assert_eq!(reg.cpu_address, None);
+        assert_eq!(reg.address, 3);
+        assert_eq!(reg.size, None);
+        assert!(matches!(
+            dev.translate_address(reg.child_bus_address),
+            Err(FdtError::NotMemoryMapped)
+        ));
+
+        let bus1 = node("/bus1");
+        let address = CellAddress::from(0x1234u32);
+        assert_eq!(bus1.translate_address(address).unwrap(), address);
+    }
+
+    #[test]
+    fn test_dma() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let node = |path| fdt.find_nodes(path).next().unwrap();
+
+        let uart = node("/soc/serial@7e201000");
+        let cpu = CellAddress::from(0x1000u32);
+        let dma = uart.cpu_to_dma(cpu).unwrap();
+        assert_eq!(dma, 0xc000_1000);
+        assert_eq!(uart.dma_to_cpu(dma).unwrap(), cpu);
+        assert!(matches!(
+            uart.cpu_to_dma(CellAddress::from(0x8000_0000u32)),
+            Err(FdtError::NoMatchingRange)
+        ));
+        let windows = uart.dma_windows().collect::<Vec<_>>();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].cpu_address, 0);
+        assert_eq!(windows[0].dma_address, 0xc000_0000);
+        assert_eq!(
+            windows[0].cpu_end(),
+            Some(CellAddress::from(0x4000_0000u32))
+        );
+        assert_eq!(windows[1].cpu_address, 0xfc00_0000);
+        assert_eq!(windows[1].dma_address, 0x7c00_0000);
+
+        // `dma-ranges;` under /soc maps its children unchanged
+        let firmware = node("/soc/firmware");
+        assert_eq!(firmware.dma_ranges().unwrap().count(), 0);
+        let clocks = firmware.children().next().unwrap();
+        assert_eq!(clocks.cpu_to_dma(cpu).unwrap(), 0xc000_1000);
+        assert_eq!(clocks.dma_windows().count(), 2);
+
+        let mmc = node("/emmc2bus").children().next().unwrap();
+        assert_eq!(mmc.cpu_to_dma(cpu).unwrap(), 0xc000_1000);
+        let windows = mmc.dma_windows().collect::<Vec<_>>();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].size, 0x4000_0000);
+        assert!(!mmc.is_dma_coherent());
+
+        // no bus has `dma-ranges` above the root children
+        let root_child = node("/soc");
+        assert_eq!(root_child.cpu_to_dma(cpu).unwrap(), cpu);
+        assert_eq!(root_child.dma_windows().count(), 0);
+
+        let fdt = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let soc_child = fdt
+            .find_nodes("/soc")
+            .next()
+            .unwrap()
+            .children()
+            .next()
+            .unwrap();
+        assert!(soc_child.is_dma_coherent());
+        assert!(!fdt.find_nodes("/cpus").next().unwrap().is_dma_coherent());
+
+        let data = Builder::default()
+            .begin("")
+            .begin("bus")
+            .prop("dma-coherent", &[])
+            .begin("dev-a")
+            .end()
+            .begin("sub")
+            .prop("dma-noncoherent", &[])
+            .begin("dev-b")
+            .end()
+            .end()
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        assert!(fdt
+            .find_nodes("/bus/dev-a")
+            .next()
+            .unwrap()
+            .is_dma_coherent());
+        assert!(!fdt
+            .find_nodes("/bus/sub/dev-b")
+            .next()
+            .unwrap()
+            .is_dma_coherent());
+    }
+
+    #[test]
+    fn test_map_interrupt() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let pci = fdt
+            .find_compatible(&["brcm,bcm2711-pcie"])
+            .next()
+            .unwrap()
+            .into_pci()
+            .unwrap();
+        let irq = pci.map_intx(0, 0, 0, PciIntPin::C).unwrap();
+        assert!(irq
+            .controller
+            .node
+            .compatibles()
+            .any(|c| c == "arm,gic-400"));
+        assert_eq!(&*irq.specifier, &[0, 0x91, 4]);
+
+        let fdt = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let pci = fdt
+            .find_compatible(&["pci-host-ecam-generic"])
+            .next()
+            .unwrap()
+            .into_pci()
+            .unwrap();
+        // the mask ignores bus, device and function
+        let irq = pci.map_intx(1, 3, 2, PciIntPin::D).unwrap();
+        assert_eq!(irq.controller.node.name, "interrupt-controller@30800000");
+        assert_eq!(&*irq.specifier, &[0, 7, 4]);
+
+        let data = Builder::default()
+            .begin("")
+            .begin("intc")
+            .cells("phandle", &[1])
+            .prop("interrupt-controller", &[])
+            .cells("#interrupt-cells", &[2])
+            .cells("#address-cells", &[0])
+            .end()
+            .begin("nexus")
+            .cells("phandle", &[2])
+            .cells("#interrupt-cells", &[1])
+            .cells("#address-cells", &[0])
+            .cells("interrupt-map", &[3, 1, 7, 1])
+            .end()
+            .begin("connector")
+            .cells("phandle", &[3])
+            .cells("#interrupt-cells", &[1])
+            .cells("#address-cells", &[1])
+            .cells("interrupt-map-mask", &[0, 0xf])
+            .cells("interrupt-map", &[0, 1, 2, 3, 0, 2, 1, 9, 4])
+            .end()
+            .begin("router")
+            .cells("interrupt-parent", &[3])
+            .cells("#interrupt-cells", &[1])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let node = |path| fdt.find_nodes(path).next().unwrap();
+        let connector = node("/connector");
+
+        let irq = connector.map_interrupt(&[0x100], &[0x11]).unwrap();
+        assert_eq!(irq.controller.node.name, "intc");
+        assert_eq!(&*irq.specifier, &[7, 1]);
+        let irq = connector.map_interrupt(&[], &[2]).unwrap();
+        assert_eq!(&*irq.specifier, &[9, 4]);
+        let irq = node("/router").map_interrupt(&[0x100], &[1]).unwrap();
+        assert_eq!(&*irq.specifier, &[7, 1]);
+
+        assert!(matches!(
+            connector.map_interrupt(&[0], &[5]),
+            Err(FdtError::NotFound("interrupt-map entry"))
+        ));
+        assert!(matches!(
+            connector.map_interrupt(&[0], &[1, 0]),
+            Err(FdtError::BadCellSize(2))
+        ));
+    }
+
+    #[test]
+    fn test_interrupt_specs() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let usb = fdt.find_nodes("/soc/usb@7e980000").next().unwrap();
+        let specs = usb
+            .interrupt_specs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].name, Some("usb"));
+        assert_eq!(specs[0].specifier.iter().collect::<Vec<_>>(), [0, 0x49, 4]);
+        assert_eq!(specs[1].name, Some("soft"));
+        assert!(specs[1]
+            .parent
+            .node
+            .compatibles()
+            .any(|c| c == "arm,gic-400"));
+
+        let pcie = fdt.find_compatible(&["brcm,bcm2711-pcie"]).next().unwrap();
+        let msi = pcie.interrupt_by_name("msi").unwrap();
+        assert_eq!(msi.specifier.iter().collect::<Vec<_>>(), [0, 0x94, 4]);
+        assert!(pcie.interrupt_by_name("intx").is_err());
+
+        let data = Builder::default()
+            .begin("")
+            .begin("plic")
+            .cells("phandle", &[1])
+            .prop("interrupt-controller", &[])
+            .cells("#interrupt-cells", &[1])
+            .end()
+            .begin("gpio")
+            .cells("phandle", &[2])
+            .prop("interrupt-controller", &[])
+            .cells("#interrupt-cells", &[2])
+            .end()
+            .begin("dev")
+            .cells("interrupt-parent", &[1])
+            .cells("interrupts", &[3])
+            .cells("interrupts-extended", &[1, 10, 2, 5, 8])
+            .string("interrupt-names", "irq\0wakeup")
+            .end()
+            .begin("bad")
+            .cells("interrupts-extended", &[1, 10, 2, 5])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+        let specs = dev
+            .interrupt_specs()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].parent.node.name, "plic");
+        assert_eq!(specs[0].specifier.iter().collect::<Vec<_>>(), [10]);
+        let wakeup = dev.interrupt_by_name("wakeup").unwrap();
+        assert_eq!(wakeup.parent.node.name, "gpio");
+        assert_eq!(wakeup.specifier.iter().collect::<Vec<_>>(), [5, 8]);
+
+        let bad = fdt.find_nodes("/bad").next().unwrap();
+        let mut specs = bad.interrupt_specs();
+        assert!(specs.next().unwrap().is_ok());
+        assert!(matches!(specs.next(), Some(Err(FdtError::Eof))));
+        assert!(specs.next().is_none());
+    }
+
+    #[test]
+    fn test_interrupt_specifier() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let gic = fdt
+            .find_nodes("/soc/interrupt-controller@40041000")
+            .next()
+            .unwrap();
+        let timer = gic.interrupt_specs().next().unwrap().unwrap();
+        let irq = timer.decode().unwrap();
+        assert_eq!(irq.hwirq, 25);
+        assert_eq!(irq.gic, Some(GicInterrupt::Ppi));
+        assert_eq!(irq.trigger, Some(Trigger::Level));
+        assert_eq!(irq.polarity, Some(Polarity::High));
+        assert_eq!(irq.ppi_cpu_mask, Some(0xf));
+
+        let usb = fdt.find_nodes("/soc/usb@7e980000").next().unwrap();
+        let irq = usb.interrupt_by_name("usb").unwrap().decode().unwrap();
+        assert_eq!(irq.hwirq, 0x49 + 32);
+        assert_eq!(irq.gic, Some(GicInterrupt::Spi));
+        assert_eq!(irq.ppi_cpu_mask, None);
+
+        let pci = fdt
+            .find_compatible(&["brcm,bcm2711-pcie"])
+            .next()
+            .unwrap()
+            .into_pci()
+            .unwrap();
+        let irq = pci.map_intx(0, 0, 0, PciIntPin::A).unwrap();
+        assert_eq!(irq.decode().unwrap().hwirq, 0x8f + 32);
+
+        let data = Builder::default()
+            .begin("")
+            .begin("armctrl")
+            .cells("phandle", &[1])
+            .string("compatible", "brcm,bcm2835-armctrl-ic")
+            .prop("interrupt-controller", &[])
+            .cells("#interrupt-cells", &[2])
+            .end()
+            .begin("plic")
+            .cells("phandle", &[2])
+            .string("compatible", "sifive,plic-1.0.0")
+            .prop("interrupt-controller", &[])
+            .cells("#interrupt-cells", &[1])
+            .end()
+            .begin("gpio")
+            .cells("phandle", &[3])
+            .prop("interrupt-controller", &[])
+            .cells("#interrupt-cells", &[2])
+            .end()
+            .begin("dev")
+            .cells("interrupts-extended", &[1, 2, 5, 2, 17, 3, 4, 2, 3, 4, 5])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+        let irqs = dev
+            .interrupt_specs()
+            .map(|spec| spec.unwrap().decode())
+            .collect::<Vec<_>>();
+        let armctrl = irqs[0].as_ref().unwrap();
+        assert_eq!(armctrl.hwirq, 2 * 32 + 5);
+        assert_eq!(armctrl.trigger, None);
+        let plic = irqs[1].as_ref().unwrap();
+        assert_eq!(plic.hwirq, 17);
+        assert_eq!(plic.gic, None);
+        let gpio = irqs[2].as_ref().unwrap();
+        assert_eq!(gpio.hwirq, 4);
+        assert_eq!(gpio.trigger, Some(Trigger::Edge));
+        assert_eq!(gpio.polarity, Some(Polarity::Low));
+        // IRQ_TYPE_EDGE_RISING | IRQ_TYPE_LEVEL_HIGH is not a valid sense
+        assert!(matches!(irqs[3], Err(FdtError::BadCell)));
+
+        let data = Builder::default()
+            .begin("")
+            .begin("gic")
+            .cells("phandle", &[1])
+            .string("compatible", "arm,gic-v3")
+            .prop("interrupt-controller", &[])
+            .cells("#interrupt-cells", &[3])
+            .end()
+            .begin("dev")
+            .cells(
+                "interrupts-extended",
+                &[1, 0xa110c8ed, 8192, 1, 1, 0xa0a0, 8192, 1],
+            )
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+        let mut specs = dev.interrupt_specs();
+        let lpi = specs.next().unwrap().unwrap().decode().unwrap();
+        assert_eq!(lpi.gic, Some(GicInterrupt::Lpi));
+        assert_eq!(lpi.hwirq, 8192);
+        assert_eq!(lpi.trigger, Some(Trigger::Edge));
+        assert!(matches!(
+            specs.next().unwrap().unwrap().decode(),
+            Err(FdtError::BadCell)
+        ));
+    }
+
+    #[test]
+    fn test_interrupt_cascade() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let controllers = fdt
+            .interrupt_controllers()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let gic = &controllers[0];
+        assert_eq!(gic.node.name, "interrupt-controller@40041000");
+        assert_eq!(gic.kind(), InterruptControllerKind::ArmGic);
+        assert!(gic.is_root());
+        assert_eq!(gic.depth().unwrap(), 0);
+        assert!(controllers[1..]
+            .iter()
+            .all(|c| !c.is_root() && c.root().unwrap().node.offset() == gic.node.offset()));
+
+        let gpio = controllers
+            .iter()
+            .find(|c| c.node.name.starts_with("gpio@"))
+            .unwrap();
+        assert_eq!(gpio.kind(), InterruptControllerKind::Gpio);
+        assert_eq!(gpio.parent().unwrap().node.offset(), gic.node.offset());
+        let l2 = controllers
+            .iter()
+            .find(|c| c.node.name == "interrupt-controller@7ef00100")
+            .unwrap();
+        assert_eq!(l2.kind(), InterruptControllerKind::BrcmL2);
+
+        let fdt = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let gic = fdt.interrupt_controllers().next().unwrap().unwrap();
+        assert_eq!(gic.kind(), InterruptControllerKind::ArmGicV3);
+
+        let data = Builder::default()
+            .begin("")
+            .begin("root-intc")
+            .cells("phandle", &[1])
+            .prop("interrupt-controller", &[])
+            .end()
+            .begin("mux")
+            .cells("phandle", &[2])
+            .prop("interrupt-controller", &[])
+            .cells("interrupt-parent", &[3])
+            .end()
+            .begin("chip")
+            .cells("phandle", &[3])
+            .prop("interrupt-controller", &[])
+            .cells("interrupt-parent", &[1])
+            .end()
+            .begin("loop-a")
+            .cells("phandle", &[4])
+            .prop("interrupt-controller", &[])
+            .cells("interrupt-parent", &[5])
+            .end()
+            .begin("loop-b")
+            .cells("phandle", &[5])
+            .prop("interrupt-controller", &[])
+            .cells("interrupt-parent", &[4])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let controllers = fdt.interrupt_controllers().collect::<Vec<_>>();
+        let names = controllers
+            .iter()
+            .map(|c| c.as_ref().map(|c| c.node.name))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            names[..],
+            [
+                Ok("root-intc"),
+                Ok("chip"),
+                Ok("mux"),
+                Err(FdtError::InterruptParentLoop),
+                Err(FdtError::InterruptParentLoop),
+            ]
+        ));
+
+        let data = Builder::default()
+            .begin("")
+            .begin("leaf")
+            .prop("interrupt-controller", &[])
+            .cells("interrupt-parent", &[2])
+            .end()
+            .begin("mid")
+            .cells("phandle", &[2])
+            .prop("interrupt-controller", &[])
+            .cells("interrupt-parent", &[1])
+            .end()
+            .begin("side")
+            .prop("interrupt-controller", &[])
+            .cells("interrupt-parent", &[2])
+            .end()
+            .begin("top")
+            .cells("phandle", &[1])
+            .prop("interrupt-controller", &[])
+            .end()
+            .end()
+            .build();
+        let later = Fdt::from_bytes(&data).unwrap();
+        let names = later
+            .interrupt_controllers()
+            .map(|c| c.unwrap().node.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["top", "mid", "leaf", "side"]);
+
+        let node = |path| InterruptController {
+            node: fdt.find_nodes(path).next().unwrap(),
+        };
+        let cascade = node("/mux")
+            .cascade()
+            .map(|c| c.unwrap().node.name)
+            .collect::<Vec<_>>();
+        assert_eq!(cascade, ["chip", "root-intc"]);
+
+        let mut cascade = node("/loop-a").cascade();
+        assert_eq!(cascade.next().unwrap().unwrap().node.name, "loop-b");
+        assert!(matches!(
+            cascade.next(),
+            Some(Err(FdtError::InterruptParentLoop))
+        ));
+        assert!(cascade.next().is_none());
+        assert!(node("/loop-b").root().is_err());
+    }
+
+    #[test]
+    fn test_phandle_with_args() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let firmware = fdt.find_nodes("/soc/firmware").next().unwrap();
+        let mbox = firmware
+            .parse_phandle_with_args("mboxes", "#mbox-cells")
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(mbox.provider.name, "mailbox@7e00b880");
+        assert!(mbox.args.is_empty());
+
+        let usb = fdt.find_nodes("/soc/usb@7e980000").next().unwrap();
+        let domain = usb
+            .parse_phandle_with_args("power-domains", "#power-domain-cells")
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(&*domain.args, &[6]);
+        let phy = usb
+            .parse_phandle_with_args_by_name("phys", "#phy-cells", "usb2-phy")
+            .unwrap();
+        assert!(phy.args.is_empty());
+
+        let data = Builder::default()
+            .begin("")
+            .begin("rst")
+            .cells("phandle", &[1])
+            .cells("#reset-cells", &[2])
+            .end()
+            .begin("mbox")
+            .cells("phandle", &[2])
+            .end()
+            .begin("dev")
+            .cells("resets", &[1, 3, 4, 0, 1, 5, 6])
+            .string("reset-names", "core\0unused\0bus")
+            .cells("mboxes", &[2, 7, 2, 8])
+            .string("mbox-names", "tx\0rx")
+            .cells("dangling", &[9, 1])
+            .cells("truncated", &[1, 3])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+
+        let mut resets = dev.parse_phandle_with_args("resets", "#reset-cells");
+        assert_eq!(&*resets.next().unwrap().unwrap().args, &[3, 4]);
+        assert!(matches!(resets.next(), Some(Err(FdtError::NullPhandle))));
+        assert_eq!(&*resets.next().unwrap().unwrap().args, &[5, 6]);
+        assert!(resets.next().is_none());
+        let bus = dev
+            .parse_phandle_with_args_by_name("resets", "#reset-cells", "bus")
+            .unwrap();
+        assert_eq!(bus.provider.name, "rst");
+        assert_eq!(&*bus.args, &[5, 6]);
+
+        // no `#mbox-cells`, the binding fixes one cell
+        assert!(matches!(
+            dev.parse_phandle_with_args("mboxes", "#mbox-cells").next(),
+            Some(Err(FdtError::MissingProperty))
+        ));
+        let rx = dev
+            .parse_phandle_with_fixed_args("mboxes", 1)
+            .nth(1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(&*rx.args, &[8]);
+
+        let mut dangling = dev.parse_phandle_with_args("dangling", "#reset-cells");
+        assert!(matches!(
+            dangling.next(),
+            Some(Err(FdtError::DanglingPhandle(p))) if p == Phandle::from(9)
+        ));
+        assert!(dangling.next().is_none());
+        assert!(matches!(
+            dev.parse_phandle_with_args("truncated", "#reset-cells")
+                .next(),
+            Some(Err(FdtError::Eof))
+        ));
+    }
+
+    #[test]
+    fn test_specifier_map() {
+        let data = Builder::default()
+            .begin("")
+            .begin("gpio")
+            .cells("phandle", &[1])
+            .prop("gpio-controller", &[])
+            .cells("#gpio-cells", &[2])
+            .end()
+            .begin("header")
+            .cells("phandle", &[2])
+            .cells("#gpio-cells", &[2])
+            .cells("gpio-map", &[0, 0, 1, 12, 0, 1, 0, 1, 13, 0])
+            .cells("gpio-map-mask", &[0xf, 0])
+            .cells("gpio-map-pass-thru", &[0, 0xff])
+            .end()
+            .begin("mikrobus")
+            .cells("phandle", &[3])
+            .cells("#gpio-cells", &[2])
+            .cells("gpio-map", &[5, 0, 2, 0x11, 0])
+            .cells("gpio-map-mask", &[0xff, 0])
+            .cells("gpio-map-pass-thru", &[0, 0xffff_ffff])
+            .end()
+            .begin("led")
+            .cells("gpios", &[3, 5, 1, 2, 0, 8, 1, 20, 0])
+            .cells("bad-gpios", &[3, 6, 0])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let led = fdt.find_nodes("/led").next().unwrap();
+
+        let gpios = led
+            .parse_phandle_with_args_map("gpios", "gpio")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(gpios.len(), 3);
+        // mikrobus 5 -> header 1 -> gpio 13, flags passed through both maps
+        assert_eq!(gpios[0].provider.name, "gpio");
+        assert_eq!(&*gpios[0].args, &[13, 1]);
+        // bit 3 of the line is masked out
+        assert_eq!(&*gpios[1].args, &[12, 8]);
+        assert_eq!(&*gpios[2].args, &[20, 0]);
+
+        let header = fdt.find_nodes("/header").next().unwrap();
+        let gpio = header.map_specifier("gpio", &[1, 4]).unwrap();
+        assert_eq!(&*gpio.args, &[13, 4]);
+        assert!(matches!(
+            header.map_specifier("gpio", &[1]),
+            Err(FdtError::BadCellSize(1))
+        ));
+        assert!(matches!(
+            led.parse_phandle_with_args_map("bad-gpios", "gpio").next(),
+            Some(Err(FdtError::NotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_gpio() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let led = fdt.find_nodes("/leds/led-pwr").next().unwrap();
+        let gpio = led.gpios(None).next().unwrap().unwrap();
+        assert_eq!(gpio.controller.name, "gpio");
+        assert_eq!(gpio.line, 2);
+        assert!(gpio.flags.is_active_low());
+        assert_eq!(gpio.controller.gpio_line_name(2), Some("PWR_LED_OFF"));
+        assert_eq!(gpio.controller.gpio_line_by_name("SD_PWR_ON"), Some(6));
+
+        let spi = fdt.find_nodes("/soc/spi@7e204000").next().unwrap();
+        let cs = spi
+            .gpios(Some("cs"))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(cs.iter().map(|cs| cs.line).collect::<Vec<_>>(), [8, 7]);
+        assert!(cs.iter().all(|cs| cs.flags.is_active_low()));
+        assert_eq!(spi.gpios(Some("reset")).count(), 0);
+
+        let gpio = &cs[0].controller;
+        let range = gpio.gpio_ranges().next().unwrap().unwrap();
+        assert_eq!(range.pinctrl.offset(), gpio.offset());
+        assert_eq!(range.count, 58);
+        assert_eq!(range.pin_of(57), Some(57));
+        assert_eq!(range.pin_of(58), None);
+
+        let data = Builder::default()
+            .begin("")
+            .begin("gpio")
+            .cells("phandle", &[1])
+            .prop("gpio-controller", &[])
+            .cells("#gpio-cells", &[2])
+            .begin("wifi-en")
+            .prop("gpio-hog", &[])
+            .cells("gpios", &[3, 0, 4, 6])
+            .prop("output-high", &[])
+            .string("line-name", "WL_ON")
+            .end()
+            .begin("broken")
+            .prop("gpio-hog", &[])
+            .cells("gpios", &[5, 0])
+            .end()
+            .end()
+            .begin("banked")
+            .cells("phandle", &[2])
+            .prop("gpio-controller", &[])
+            .cells("#gpio-cells", &[3])
+            .begin("hog")
+            .prop("gpio-hog", &[])
+            .cells("gpios", &[1, 7, 1])
+            .prop("input", &[])
+            .end()
+            .end()
+            .begin("dev")
+            .cells("reset-gpio", &[1, 9, 0x12])
+            .cells("enable-gpios", &[2, 3, 14, 1])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let reset = fdt
+            .find_nodes("/dev")
+            .next()
+            .unwrap()
+            .gpios(Some("reset"))
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(reset.line, 9);
+        assert!(reset.flags.is_open_source());
+        assert!(!reset.flags.is_open_drain());
+        assert!(reset.flags.is_pull_up());
+        assert_eq!(&*reset.specifier, &[9, 0x12]);
+
+        let enable = fdt
+            .find_nodes("/dev")
+            .next()
+            .unwrap()
+            .gpios(Some("enable"))
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(enable.line, 14);
+        assert!(enable.flags.is_active_low());
+        assert_eq!(&*enable.specifier, &[3, 14, 1]);
+        let banked = fdt.find_nodes("/banked").next().unwrap();
+        let hog = banked.gpio_hogs().next().unwrap().unwrap();
+        assert_eq!(
+            hog.lines().collect::<Vec<_>>(),
+            [(7, GpioFlags(GpioFlags::ACTIVE_LOW))]
+        );
+
+        let gpio = fdt.find_nodes("/gpio").next().unwrap();
+        let hogs = gpio.gpio_hogs().collect::<Vec<_>>();
+        let hog = hogs[0].as_ref().unwrap();
+        assert_eq!(hog.state, HogState::OutputHigh);
+        assert_eq!(hog.line_name, "WL_ON");
+        let lines = hog.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], (3, GpioFlags(0)));
+        assert!(lines[1].1.is_open_drain());
+        assert!(hogs[1].is_err());
+    }
+
+    #[test]
+    fn test_dma_channels() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let spi = fdt.find_nodes("/soc/spi@7e204000").next().unwrap();
+        let channels = spi.dma_channels().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].name, Some("tx"));
+        assert_eq!(&*channels[0].specifier, &[6]);
+        let rx = spi.dma_channel_by_name("rx").unwrap();
+        assert_eq!(&*rx.specifier, &[7]);
+        assert_eq!(rx.controller.offset(), channels[0].controller.offset());
+        let limits = rx.limits();
+        assert_eq!(limits.channel_mask, Some(0x7f5));
+        assert_eq!(limits.channels, None);
+        assert!(spi.dma_channel_by_name("cmd").is_err());
+
+        let fdt = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let ddma = fdt.find_compatible(&["phytium,ddma"]).next().unwrap();
+        assert_eq!(ddma.dma_limits().channels, Some(8));
+    }
+
+    #[test]
+    fn test_thermal_zones() {
+        let fdt = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let zones = fdt.thermal_zones().unwrap();
+        assert_eq!(
+            zones.zones().map(|z| z.name()).collect::<Vec<_>>(),
+            ["sensor0", "sensor1"]
+        );
+        let zone = zones.zone("sensor0").unwrap();
+        assert_eq!(zone.polling_delay(), Some(1000));
+        assert_eq!(zone.polling_delay_passive(), Some(100));
+        let sensor = zone.sensors().next().unwrap().unwrap();
+        assert_eq!(&*sensor.args, &[0]);
+
+        let trips = zone.trips().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(trips.len(), 3);
+        assert_eq!(trips[0].kind, TripType::Critical);
+        assert_eq!(trips[0].temperature, 100_000);
+        assert_eq!(trips[0].hysteresis, 2000);
+        assert_eq!(trips[2].kind, TripType::Passive);
+        assert_eq!(trips[2].temperature, 90_000);
+
+        let map = zone.cooling_maps().next().unwrap();
+        let trip = map.trip().unwrap();
+        assert_eq!(trip.node.offset(), trips[2].node.offset());
+        assert_eq!(map.contribution(), None);
+        let devices = map
+            .cooling_devices()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].min_state, None);
+        assert_eq!(devices[0].max_state, None);
+        assert_eq!(zones.zone("sensor1").unwrap().cooling_maps().count(), 0);
+
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let zone = fdt.thermal_zones().unwrap().zone("cpu-thermal").unwrap();
+        assert_eq!(zone.coefficients().collect::<Vec<_>>(), [-487, 410040]);
+        assert!(zone.sensors().next().unwrap().unwrap().args.is_empty());
+        assert_eq!(zone.trips().next().unwrap().unwrap().temperature, 110_000);
+    }
+
+    #[test]
+    fn test_clock_consumer() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let uart = fdt.find_nodes("/soc/serial@7e201000").next().unwrap();
+        let pclk = uart.clock_by_name("apb_pclk").unwrap();
+        assert_eq!(pclk.node.name, "cprman@7e101000");
+        assert_eq!(pclk.select, 0x14);
+        assert_eq!(&*pclk.specifier, &[0x14]);
+        assert_eq!(uart.clocks().next().unwrap().name, Some("uartclk"));
+        assert!(uart.clock_by_name("baud").is_err());
+
+        let cprman = fdt.find_nodes("/soc/cprman@7e101000").next().unwrap();
+        let osc = cprman.clocks().next().unwrap();
+        assert!(osc.specifier.is_empty());
+        assert_eq!(osc.output_name(), Some("osc"));
+
+        let pwm = fdt.find_nodes("/soc/pwm@7e20c000").next().unwrap();
+        let assigned = pwm
+            .assigned_clocks()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].clock.select, 0x1e);
+        assert_eq!(assigned[0].rate, Some(10_000_000));
+        assert!(assigned[0].parent.is_none());
+
+        let data = Builder::default()
+            .begin("")
+            .begin("pll")
+            .cells("phandle", &[1])
+            .cells("#clock-cells", &[1])
+            .string("clock-output-names", "pll_a\0pll_b\0pll_c")
+            .end()
+            .begin("dev")
+            .cells("clocks", &[1, 2, 1, 0])
+            .cells("assigned-clocks", &[1, 0, 1, 1, 1, 2])
+            .cells("assigned-clock-parents", &[0, 1, 2])
+            .cells("assigned-clock-rates-u64", &[0, 0, 0, 0, 1, 0])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+        let clocks = dev.clocks().collect::<Vec<_>>();
+        assert_eq!(clocks[0].output_name(), Some("pll_c"));
+        assert_eq!(clocks[1].name, None);
+        let assigned = dev
+            .assigned_clocks()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(assigned.len(), 3);
+        assert!(assigned[0].parent.is_none());
+        assert_eq!(assigned[0].rate, None);
+        assert_eq!(assigned[1].parent.as_ref().unwrap().select, 2);
+        assert_eq!(assigned[2].rate, Some(1 << 32));
+        assert!(assigned[2].parent.is_none());
+    }
+
+    #[test]
+    fn test_clock_tree() {
+        let fdt = Fdt::from_bytes(TEST_PHYTIUM_FDT).unwrap();
+        let tree = fdt.clock_tree();
+        let uart = fdt.find_nodes("/soc/uart@28014000").next().unwrap();
+        assert_eq!(tree.consumer_rate(&uart, Some("uartclk")), Some(50_000_000));
+        assert!(tree
+            .providers()
+            .all(|p| p.find_property("#clock-cells").is_some()));
+
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        let tree = fdt.clock_tree();
+        let pwm = fdt.find_nodes("/soc/pwm@7e20c000").next().unwrap();
+        assert_eq!(tree.consumer_rate(&pwm, None), Some(10_000_000));
+        // cprman has several inputs and no rate of its own
+        let uart = fdt.find_nodes("/soc/serial@7e201000").next().unwrap();
+        let clock = uart.clock_by_name("uartclk").unwrap();
+        assert_eq!(tree.rate(&clock), None);
+        assert_eq!(tree.chain(&clock).count(), 0);
+
+        let data = Builder::default()
+            .begin("")
+            .begin("osc")
+            .cells("phandle", &[1])
+            .string("compatible", "fixed-clock")
+            .cells("#clock-cells", &[0])
+            .cells("clock-frequency", &[24_000_000])
+            .end()
+            .begin("half")
+            .cells("phandle", &[2])
+            .string("compatible", "fixed-factor-clock")
+            .cells("#clock-cells", &[0])
+            .cells("clocks", &[1])
+            .cells("clock-mult", &[1])
+            .cells("clock-div", &[2])
+            .end()
+            .begin("triple")
+            .cells("phandle", &[3])
+            .string("compatible", "fixed-factor-clock")
+            .cells("#clock-cells", &[0])
+            .cells("clocks", &[2])
+            .cells("clock-mult", &[3])
+            .cells("clock-div", &[1])
+            .end()
+            .begin("dev")
+            .cells("clocks", &[3, 2])
+            .string("clock-names", "core\0bus")
+            .end()
+            .begin("optional")
+            .cells("clocks", &[0, 2])
+            .string("clock-names", "ref\0bus")
+            .end()
+            .begin("mux")
+            .cells("phandle", &[5])
+            .cells("#clock-cells", &[0])
+            .cells("clocks", &[1, 3])
+            .end()
+            .begin("board")
+            .cells("clocks", &[5])
+            .cells("assigned-clocks", &[5])
+            .cells("assigned-clock-parents", &[3])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let tree = fdt.clock_tree();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+        assert_eq!(tree.consumer_rate(&dev, Some("core")), Some(36_000_000));
+        assert_eq!(tree.consumer_rate(&dev, Some("bus")), Some(12_000_000));
+        let core = dev.clock_by_name("core").unwrap();
+        let chain = tree.chain(&core).map(|c| c.node.name).collect::<Vec<_>>();
+        assert_eq!(chain, ["half", "osc"]);
+
+        let optional = fdt.find_nodes("/optional").next().unwrap();
+        let clocks = optional
+            .try_clocks()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(clocks.len(), 1);
+        assert_eq!(clocks[0].name, Some("bus"));
+        assert_eq!(optional.clocks().count(), 1);
+        assert_eq!(tree.consumer_rate(&optional, Some("bus")), Some(12_000_000));
+        assert!(matches!(
+            optional.clock_by_name("ref"),
+            Err(FdtError::NotFound("clock"))
+        ));
+
+        // The mux has two inputs, the board picks one
+        let board = fdt.find_nodes("/board").next().unwrap();
+        let mux = board.clocks().next().unwrap();
+        let chain = tree.chain(&mux).map(|c| c.node.name).collect::<Vec<_>>();
+        assert_eq!(chain, ["triple", "half", "osc"]);
+        assert_eq!(tree.rate(&mux), Some(36_000_000));
+        let mux = fdt.find_nodes("/mux").next().unwrap();
+        assert_eq!(mux.clocks().count(), 2);
+
+        // More assigned rates than the tree keeps up front
+        let assigned = (0..20).flat_map(|i| [4, i]).collect::<Vec<_>>();
+        let rates = (0..20).map(|i| 1000 + i).collect::<Vec<_>>();
+        let data = Builder::default()
+            .begin("")
+            .begin("pll")
+            .cells("phandle", &[4])
+            .cells("#clock-cells", &[1])
+            .cells("assigned-clocks", &assigned)
+            .cells("assigned-clock-rates", &rates)
+            .end()
+            .begin("dev")
+            .cells("clocks", &[4, 3, 4, 19])
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let tree = fdt.clock_tree();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+        let rates = dev
+            .clocks()
+            .map(|clock| tree.rate(&clock))
+            .collect::<Vec<_>>();
+        assert_eq!(rates, [Some(1003), Some(1019)]);
+    }
+
+    #[test]
+    fn test_resets() {
+        let fdt = Fdt::from_bytes(TEST_FDT).unwrap();
+        assert_eq!(fdt.reset_controllers().count(), 3);
+        let hdmi = fdt.find_nodes("/soc/hdmi@7ef05700").next().unwrap();
+        let reset = hdmi.resets().next().unwrap().unwrap();
+        assert_eq!(reset.controller.node.name, "clock@7ef00000");
+        assert_eq!(reset.controller.reset_cells().unwrap(), 1);
+        assert_eq!(reset.id, 1);
+        assert_eq!(reset.name, None);
+
+        let data = Builder::default()
+            .begin("")
+            .begin("rst")
+            .cells("phandle", &[1])
+            .string("compatible", "vendor,rst")
+            .cells("#reset-cells", &[1])
+            .end()
+            .begin("banked")
+            .cells("phandle", &[2])
+            .cells("#reset-cells", &[2])
+            .end()
+            .begin("dev")
+            .cells("resets", &[1, 4, 1, 7])
+            .string("reset-names", "core\0bus")
+            .end()
+            .begin("optional")
+            .cells("resets", &[0, 2, 1, 3])
+            .string("reset-names", "phy\0mac")
+            .end()
+            .begin("bad")
+            .cells("resets", &[1])
+            .string("reset-names", "core")
+            .end()
+            .begin("dangling")
+            .cells("resets", &[9, 1, 1, 2])
+            .string("reset-names", "phy\0mac")
+            .end()
+            .end()
+            .build();
+        let fdt = Fdt::from_bytes(&data).unwrap();
+        let dev = fdt.find_nodes("/dev").next().unwrap();
+        assert_eq!(dev.reset_by_name("bus").unwrap().id, 7);
+        assert_eq!(dev.optional_reset_by_name("core").unwrap().unwrap().id, 4);
+        assert!(dev.optional_reset_by_name("phy").unwrap().is_none());
+        assert!(matches!(
+            dev.reset_by_name("phy"),
+            Err(FdtError::NotFound("reset"))
+        ));
+
+        let bad = fdt.find_nodes("/bad").next().unwrap();
+        assert!(matches!(
+            bad.optional_reset_by_name("phy"),
+            Err(FdtError::Eof)
+        ));
+
+        let core = dev.reset_by_name("core").unwrap();
+        assert_eq!(
+            core.controller.compatibles().collect::<Vec<_>>(),
+            ["vendor,rst"]
+        );
+        assert_eq!(core.controller.line(&core.specifier).unwrap(), 4);
+        assert!(matches!(
+            core.controller.line(&[4, 0]),
+            Err(FdtError::BadCellSize(2))
+        ));
+
+        let optional = fdt.find_nodes("/optional").next().unwrap();
+        let resets = optional.resets().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(resets.len(), 1);
+        assert_eq!(resets[0].name, Some("mac"));
+        assert!(optional.optional_reset_by_name("phy").unwrap().is_none());
+        let mac = optional.reset_by_name("mac").unwrap();
+        assert_eq!(&*mac.specifier, &[1, 3]);
+        assert!(matches!(
+            mac.controller.line(&mac.specifier),
+            Err(FdtError::BadCellSize(2))
+        ));
+
+        // Only null entries are skipped, a phandle without a node is an error
+        let dangling = fdt.find_nodes("/dangling").next().unwrap();
+        assert!(matches!(
+            dangling.resets().next(),
+            Some(Err(FdtError::DanglingPhandle(_)))
+        ));
+        assert!(matches!(
+            dangling.optional_reset_by_name("mac"),
+            Err(FdtError::DanglingPhandle(_))
+        ));
+    }
 }